@@ -0,0 +1,334 @@
+use std::{collections::VecDeque, fmt};
+
+use crate::{
+    candidate::{self, Candidate},
+    chains::{chain_eliminations, link_kind, Chain},
+    consts,
+    sudoku::Sudoku,
+    Result,
+};
+
+const NUM_NODES: usize = consts::SIZE * consts::WIDTH;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AicKind {
+    /// A chain through bivalue cells only.
+    XyChain,
+    /// A chain mixing bivalue cells and bilocal digits.
+    Aic,
+    /// A chain whose ends lie in the same cell. If both ends are the same
+    /// candidate, that candidate must be true.
+    DiscontinuousNiceLoop,
+}
+
+impl fmt::Display for AicKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::XyChain => write!(f, "XY-Chain"),
+            Self::Aic => write!(f, "AIC"),
+            Self::DiscontinuousNiceLoop => write!(f, "Discontinuous Nice Loop"),
+        }
+    }
+}
+
+/// An alternating inference chain that starts and ends with a strong link,
+/// together with the candidates it eliminates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aic {
+    pub kind: AicKind,
+    pub chain: Chain,
+    pub eliminations: Vec<Candidate>,
+}
+
+impl fmt::Display for Aic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} =>", self.kind, self.chain)?;
+        self.eliminations
+            .iter()
+            .try_for_each(|candidate| write!(f, " {candidate}"))
+    }
+}
+
+/// Find all XY-Chains that eliminate at least one candidate.
+#[must_use]
+pub fn find_xy_chains(sudoku: &Sudoku) -> Vec<Aic> {
+    find_chains(sudoku, &LinkGraph::bivalue(sudoku), AicKind::XyChain)
+}
+
+/// Find all alternating inference chains over bivalue cells and bilocal
+/// digits that eliminate at least one candidate, including discontinuous nice
+/// loops.
+#[must_use]
+pub fn find_aics(sudoku: &Sudoku) -> Vec<Aic> {
+    find_chains(sudoku, &LinkGraph::full(sudoku), AicKind::Aic)
+}
+
+/// Apply the eliminations of the shortest XY-Chain, or the shortest AIC if no
+/// XY-Chain is found.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn apply_aic(sudoku: &mut Sudoku) -> Result<Option<Aic>> {
+    let shortest = |aics: Vec<Aic>| aics.into_iter().min_by_key(|aic| aic.chain.links.len());
+    let Some(aic) = shortest(find_xy_chains(sudoku)).or_else(|| shortest(find_aics(sudoku))) else {
+        return Ok(None);
+    };
+    candidate::eliminate(sudoku, &aic.eliminations)?;
+    Ok(Some(aic))
+}
+
+const fn node_id(candidate: Candidate) -> usize {
+    candidate.idx * consts::WIDTH + candidate.digit - 1
+}
+
+const fn node(id: usize) -> Candidate {
+    Candidate::new(id / consts::WIDTH, id % consts::WIDTH + 1)
+}
+
+struct LinkGraph {
+    nodes: Vec<usize>,
+    strong: Vec<Vec<usize>>,
+    weak: Vec<Vec<usize>>,
+}
+
+impl LinkGraph {
+    /// Strong links inside bivalue cells, weak links between equal digits.
+    fn bivalue(sudoku: &Sudoku) -> Self {
+        let is_bivalue = |idx: usize| sudoku.bitboard[idx].count_ones() == 2;
+        let mut graph = Self::empty();
+        for candidate in candidate::candidates(sudoku).filter(|c| is_bivalue(c.idx)) {
+            let id = node_id(candidate);
+            graph.nodes.push(id);
+            graph.strong[id] = cell_partners(sudoku, candidate).collect();
+            graph.weak[id] = digit_partners(sudoku, candidate)
+                .filter(|c| is_bivalue(c.idx))
+                .map(node_id)
+                .collect();
+        }
+        graph
+    }
+
+    /// Strong links from bivalue cells and bilocal digits, weak links between
+    /// any two candidates that exclude each other.
+    fn full(sudoku: &Sudoku) -> Self {
+        let mut graph = Self::empty();
+        for candidate in candidate::candidates(sudoku) {
+            let id = node_id(candidate);
+            graph.nodes.push(id);
+            if sudoku.bitboard[candidate.idx].count_ones() == 2 {
+                graph.strong[id].extend(cell_partners(sudoku, candidate));
+            }
+            graph.strong[id].extend(bilocal_partners(sudoku, candidate));
+            graph.strong[id].sort_unstable();
+            graph.strong[id].dedup();
+            graph.weak[id] = candidate::digits(sudoku.bitboard[candidate.idx])
+                .filter(|&digit| digit != candidate.digit)
+                .map(|digit| Candidate::new(candidate.idx, digit))
+                .chain(digit_partners(sudoku, candidate))
+                .map(node_id)
+                .collect();
+        }
+        graph
+    }
+
+    fn empty() -> Self {
+        Self {
+            nodes: vec![],
+            strong: vec![vec![]; NUM_NODES],
+            weak: vec![vec![]; NUM_NODES],
+        }
+    }
+}
+
+/// The other candidates in the same cell.
+fn cell_partners(sudoku: &Sudoku, candidate: Candidate) -> impl Iterator<Item = usize> + '_ {
+    candidate::digits(sudoku.bitboard[candidate.idx])
+        .filter(move |&digit| digit != candidate.digit)
+        .map(move |digit| node_id(Candidate::new(candidate.idx, digit)))
+}
+
+/// The same digit in cells that see this one.
+fn digit_partners(sudoku: &Sudoku, candidate: Candidate) -> impl Iterator<Item = Candidate> + '_ {
+    consts::NEIGHBORS[candidate.idx]
+        .iter()
+        .filter(move |&&idx| sudoku.bitboard[idx] & (1 << candidate.digit) > 0)
+        .map(move |&idx| Candidate::new(idx, candidate.digit))
+}
+
+/// The other cell of every unit where the digit appears exactly twice.
+fn bilocal_partners(sudoku: &Sudoku, candidate: Candidate) -> Vec<usize> {
+    let idx = candidate.idx;
+    let units = [
+        &consts::ROWS[idx / consts::WIDTH],
+        &consts::COLS[idx % consts::WIDTH],
+        &consts::CELLS[consts::CELL_LOOKUP[idx]],
+    ];
+    units
+        .into_iter()
+        .filter_map(|unit| {
+            let mut cells = candidate::cells_with_digit(sudoku, unit, candidate.digit);
+            match (cells.next(), cells.next(), cells.next()) {
+                (Some(a), Some(b), None) if a == idx => Some(b),
+                (Some(a), Some(b), None) if b == idx => Some(a),
+                _ => None,
+            }
+        })
+        .map(|other| node_id(Candidate::new(other, candidate.digit)))
+        .collect()
+}
+
+fn find_chains(sudoku: &Sudoku, graph: &LinkGraph, kind: AicKind) -> Vec<Aic> {
+    let mut aics = vec![];
+    for &start in &graph.nodes {
+        for path in shortest_paths(graph, start) {
+            let chain = Chain {
+                links: (1..path.len()).map(|i| link_kind(i - 1)).collect(),
+                nodes: path.into_iter().map(node).collect(),
+            };
+            let eliminations = chain_eliminations(sudoku, &chain);
+            if eliminations.is_empty() {
+                continue;
+            }
+            let kind = match (chain.first(), chain.last()) {
+                (Some(first), Some(last)) if kind == AicKind::Aic && first.idx == last.idx => {
+                    AicKind::DiscontinuousNiceLoop
+                }
+                _ => kind,
+            };
+            aics.push(Aic {
+                kind,
+                chain,
+                eliminations,
+            });
+        }
+    }
+    aics
+}
+
+/// Breadth first search over (node, parity) states, where an odd parity means
+/// the node was reached through a strong link. Returns the shortest path to
+/// every node from `start` onwards that ends on a strong link and spans at
+/// least three links.
+fn shortest_paths(graph: &LinkGraph, start: usize) -> Vec<Vec<usize>> {
+    let mut parent: Vec<Option<usize>> = vec![None; 2 * NUM_NODES];
+    let mut visited = vec![false; 2 * NUM_NODES];
+    let mut queue = VecDeque::from([2 * start]);
+    visited[2 * start] = true;
+    let mut paths = vec![];
+
+    while let Some(state) = queue.pop_front() {
+        let (id, parity) = (state / 2, state % 2);
+        let (next_nodes, next_parity) = if parity == 0 {
+            (&graph.strong[id], 1)
+        } else {
+            (&graph.weak[id], 0)
+        };
+        for &next_id in next_nodes {
+            let next = 2 * next_id + next_parity;
+            if visited[next] {
+                continue;
+            }
+            visited[next] = true;
+            parent[next] = Some(state);
+            queue.push_back(next);
+            if next_parity == 1 && next_id >= start {
+                let path = reconstruct(&parent, next);
+                if path.len() >= 4 && is_simple(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+    }
+    paths
+}
+
+fn reconstruct(parent: &[Option<usize>], end: usize) -> Vec<usize> {
+    let mut path = vec![end / 2];
+    let mut state = end;
+    while let Some(previous) = parent[state] {
+        path.push(previous / 2);
+        state = previous;
+    }
+    path.reverse();
+    path
+}
+
+/// Returns true if no node repeats, except for the ends of a loop.
+fn is_simple(path: &[usize]) -> bool {
+    let Some((last, body)) = path.split_last() else {
+        return true;
+    };
+    let unique = body
+        .iter()
+        .enumerate()
+        .all(|(i, id)| !body[i + 1..].contains(id));
+    unique && (body.first() == Some(last) || !body.contains(last))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::{assert_eliminations_are_sound, with_candidates};
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_xy_chain() {
+        // (1)r1c1=(2)r1c1-(2)r1c5=(3)r1c5-(3)r5c5=(1)r5c5
+        let sudoku = with_candidates(&[(0, &[1, 2]), (4, &[2, 3]), (40, &[3, 1])]);
+        let xy_chains = find_xy_chains(&sudoku);
+
+        let xy_chain = xy_chains
+            .iter()
+            .find(|aic| aic.chain.first() == Some(&Candidate::new(0, 1)))
+            .map(ToString::to_string);
+        assert_eq!(
+            xy_chain.as_deref(),
+            Some("XY-Chain: (1)r1c1=(2)r1c1-(2)r1c5=(3)r1c5-(3)r5c5=(1)r5c5 => r5c1<>1")
+        );
+    }
+
+    #[test]
+    fn test_discontinuous_nice_loop() -> Result<()> {
+        let mut sudoku = Sudoku::from_str(
+            "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..",
+        )?;
+        check_constraints(&mut sudoku)?;
+        check_constraints(&mut sudoku)?;
+
+        let aics = find_aics(&sudoku);
+        let nice_loop = aics
+            .iter()
+            .find(|aic| aic.kind == AicKind::DiscontinuousNiceLoop)
+            .map(ToString::to_string);
+        assert_eq!(
+            nice_loop.as_deref(),
+            Some("Discontinuous Nice Loop: (7)r1c2=(7)r1c3-(9)r1c3=(9)r1c2 => r1c2<>1 r1c2<>2 r1c2<>6")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_simple() {
+        assert!(is_simple(&[1, 2, 3, 4]));
+        assert!(is_simple(&[1, 2, 3, 1]));
+        assert!(!is_simple(&[1, 2, 2, 4]));
+        assert!(!is_simple(&[1, 2, 1, 4]));
+        assert!(!is_simple(&[1, 2, 3, 2]));
+    }
+
+    #[rstest]
+    #[case(
+        "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..",
+        "397514628415628973286937154978362415631845792542791386729456831163289547854173269"
+    )]
+    fn test_eliminations_are_sound(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        assert_eliminations_are_sound(input, expected, find_aics, apply_aic, |aic| {
+            &aic.eliminations
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,481 @@
+use std::fmt;
+
+use crate::{
+    candidate::{self, sees, subsets, Candidate, CellName},
+    consts,
+    sudoku::Sudoku,
+    Result,
+};
+
+/// A set of cells within a single unit and the union of their candidates. For
+/// an almost locked set there is exactly one more digit than there are cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Als {
+    pub cells: Vec<usize>,
+    pub digits: consts::BitWidth,
+}
+
+impl fmt::Display for Als {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &idx) in self.cells.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(f, "{separator}{}", CellName(idx))?;
+        }
+        write!(f, "{{")?;
+        candidate::digits(self.digits).try_for_each(|digit| write!(f, "{digit}"))?;
+        write!(f, "}}")
+    }
+}
+
+impl Als {
+    fn new(sudoku: &Sudoku, cells: Vec<usize>) -> Self {
+        let digits = cells
+            .iter()
+            .fold(0, |mask, &idx| mask | sudoku.bitboard[idx]);
+        Self { cells, digits }
+    }
+
+    const fn is_almost_locked(&self) -> bool {
+        self.digits.count_ones() as usize == self.cells.len() + 1
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.cells.iter().any(|idx| other.cells.contains(idx))
+    }
+
+    /// The cells of this set that have `digit` as a candidate.
+    fn cells_with<'a>(
+        &'a self,
+        sudoku: &'a Sudoku,
+        digit: consts::BitWidth,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.cells
+            .iter()
+            .copied()
+            .filter(move |&idx| sudoku.bitboard[idx] & (1 << digit) > 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlsKind {
+    AlsXz,
+    AlsXzDoublyLinked,
+    AlsXyWing,
+    SueDeCoq,
+}
+
+impl fmt::Display for AlsKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AlsXz => write!(f, "ALS-XZ"),
+            Self::AlsXzDoublyLinked => write!(f, "ALS-XZ (doubly linked)"),
+            Self::AlsXyWing => write!(f, "ALS-XY-Wing"),
+            Self::SueDeCoq => write!(f, "Sue de Coq"),
+        }
+    }
+}
+
+/// A pattern built from almost locked sets, together with the restricted
+/// common candidates linking the sets and the candidates it eliminates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlsStep {
+    pub kind: AlsKind,
+    pub sets: Vec<Als>,
+    pub restricted: consts::BitWidth,
+    pub eliminations: Vec<Candidate>,
+}
+
+impl fmt::Display for AlsStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.kind)?;
+        for (i, als) in self.sets.iter().enumerate() {
+            let separator = if i == 0 { " " } else { " / " };
+            write!(f, "{separator}{als}")?;
+        }
+        if self.restricted != 0 {
+            write!(f, " RCC ")?;
+            candidate::digits(self.restricted).try_for_each(|digit| write!(f, "{digit}"))?;
+        }
+        write!(f, " =>")?;
+        self.eliminations
+            .iter()
+            .try_for_each(|candidate| write!(f, " {candidate}"))
+    }
+}
+
+/// Find all almost locked sets: `n` unsolved cells in a unit with exactly
+/// `n + 1` candidates between them.
+#[must_use]
+pub fn find_almost_locked_sets(sudoku: &Sudoku) -> Vec<Als> {
+    let mut sets: Vec<Als> = candidate::units()
+        .flat_map(|unit| {
+            let unsolved: Vec<usize> = unit
+                .iter()
+                .copied()
+                .filter(|&idx| sudoku.digits[idx] == 0)
+                .collect();
+            subsets(&unsolved, unsolved.len().saturating_sub(1))
+                .into_iter()
+                .map(|cells| Als::new(sudoku, cells))
+                .filter(Als::is_almost_locked)
+        })
+        .collect();
+    sets.sort_unstable_by(|a, b| a.cells.cmp(&b.cells));
+    sets.dedup();
+    sets
+}
+
+/// Find all ALS-XZ, ALS-XY-Wing and Sue de Coq patterns that eliminate at
+/// least one candidate.
+#[must_use]
+pub fn find_als_steps(sudoku: &Sudoku) -> Vec<AlsStep> {
+    let sets = find_almost_locked_sets(sudoku);
+    let mut steps = find_als_xz(sudoku, &sets);
+    steps.extend(find_als_xy_wings(sudoku, &sets));
+    steps.extend(find_sue_de_coq(sudoku));
+    steps
+}
+
+/// Apply the eliminations of the first ALS pattern found, if any. ALS-XZ is
+/// tried before ALS-XY-Wing, and Sue de Coq last.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn apply_als_step(sudoku: &mut Sudoku) -> Result<Option<AlsStep>> {
+    let sets = find_almost_locked_sets(sudoku);
+    let Some(step) = find_als_xz(sudoku, &sets)
+        .into_iter()
+        .next()
+        .or_else(|| find_als_xy_wings(sudoku, &sets).into_iter().next())
+        .or_else(|| find_sue_de_coq(sudoku).into_iter().next())
+    else {
+        return Ok(None);
+    };
+    candidate::eliminate(sudoku, &step.eliminations)?;
+    Ok(Some(step))
+}
+
+/// Find ALS-XZ patterns.
+///
+/// Two almost locked sets are joined by a restricted common candidate `x`, so
+/// any other common digit `z` must be in one of the sets. With two restricted
+/// common candidates, both sets become locked.
+#[must_use]
+pub fn find_als_xz(sudoku: &Sudoku, sets: &[Als]) -> Vec<AlsStep> {
+    let mut steps = vec![];
+    for (i, a) in sets.iter().enumerate() {
+        for b in sets[i + 1..].iter().filter(|b| !a.overlaps(b)) {
+            let restricted = restricted_commons(sudoku, a, b);
+            let eliminations = match restricted.count_ones() {
+                1 => common_digit_eliminations(sudoku, &[a, b], a.digits & b.digits & !restricted),
+                2 => doubly_linked_eliminations(sudoku, a, b, restricted),
+                _ => continue,
+            };
+            if !eliminations.is_empty() {
+                let kind = if restricted.is_power_of_two() {
+                    AlsKind::AlsXz
+                } else {
+                    AlsKind::AlsXzDoublyLinked
+                };
+                steps.push(AlsStep {
+                    kind,
+                    sets: vec![a.clone(), b.clone()],
+                    restricted,
+                    eliminations,
+                });
+            }
+        }
+    }
+    steps
+}
+
+/// Find ALS-XY-Wings: a pivot set sharing the restricted common candidate `x`
+/// with one set and `y` with another. Any digit `z` common to the outer sets
+/// must be in one of them.
+#[must_use]
+pub fn find_als_xy_wings(sudoku: &Sudoku, sets: &[Als]) -> Vec<AlsStep> {
+    let mut steps = vec![];
+    for pivot in sets {
+        let linked: Vec<(&Als, consts::BitWidth)> = sets
+            .iter()
+            .filter(|other| !pivot.overlaps(other))
+            .map(|other| (other, restricted_commons(sudoku, pivot, other)))
+            .filter(|&(_, restricted)| restricted != 0)
+            .collect();
+        for (i, &(a, a_links)) in linked.iter().enumerate() {
+            for &(b, b_links) in linked[i + 1..].iter().filter(|(b, _)| !a.overlaps(b)) {
+                for (x, y) in single_digit_pairs(a_links, b_links) {
+                    let z = a.digits & b.digits & !((1 << x) | (1 << y));
+                    let eliminations = common_digit_eliminations(sudoku, &[a, b], z);
+                    if !eliminations.is_empty() {
+                        steps.push(AlsStep {
+                            kind: AlsKind::AlsXyWing,
+                            sets: vec![pivot.clone(), a.clone(), b.clone()],
+                            restricted: (1 << x) | (1 << y),
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    steps
+}
+
+/// Find Sue de Coq patterns.
+///
+/// The unsolved cells where a box and a line intersect, together with cells
+/// from the rest of the line and the rest of the box, hold as many cells as
+/// digits, with no digit shared between the line and box parts.
+#[must_use]
+pub fn find_sue_de_coq(sudoku: &Sudoku) -> Vec<AlsStep> {
+    let mut steps = vec![];
+    for cell in &consts::CELLS {
+        let lines = consts::ROWS.iter().chain(consts::COLS.iter());
+        for line in lines.filter(|line| line.iter().any(|idx| cell.contains(idx))) {
+            let intersection: Vec<usize> = line
+                .iter()
+                .copied()
+                .filter(|idx| cell.contains(idx) && sudoku.digits[*idx] == 0)
+                .collect();
+            if intersection.len() < 2 {
+                continue;
+            }
+            let line_rest = unsolved_outside(sudoku, line, cell);
+            let box_rest = unsolved_outside(sudoku, cell, line);
+            for core in subsets(&intersection, 3)
+                .into_iter()
+                .filter(|c| c.len() >= 2)
+            {
+                let core = Als::new(sudoku, core);
+                if (core.digits.count_ones() as usize) < core.cells.len() + 2 {
+                    continue;
+                }
+                check_sue_de_coq(
+                    sudoku,
+                    &core,
+                    (line, &line_rest),
+                    (cell, &box_rest),
+                    &mut steps,
+                );
+            }
+        }
+    }
+    steps
+}
+
+fn check_sue_de_coq(
+    sudoku: &Sudoku,
+    core: &Als,
+    (line, line_rest): (&[usize; consts::WIDTH], &[usize]),
+    (cell, box_rest): (&[usize; consts::WIDTH], &[usize]),
+    steps: &mut Vec<AlsStep>,
+) {
+    for line_cells in subsets(line_rest, 2) {
+        let line_set = Als::new(sudoku, line_cells);
+        if line_set.digits & core.digits == 0 {
+            continue;
+        }
+        for box_cells in subsets(box_rest, 2) {
+            let box_set = Als::new(sudoku, box_cells);
+            if box_set.digits & core.digits == 0 || box_set.digits & line_set.digits != 0 {
+                continue;
+            }
+            let num_cells = core.cells.len() + line_set.cells.len() + box_set.cells.len();
+            let all_digits = core.digits | line_set.digits | box_set.digits;
+            if all_digits.count_ones() as usize != num_cells {
+                continue;
+            }
+            let sets = [core, &line_set, &box_set];
+            let line_digits = (core.digits | line_set.digits) & !box_set.digits;
+            let box_digits = (core.digits | box_set.digits) & !line_set.digits;
+            let mut eliminations = outside_eliminations(sudoku, line, &sets, line_digits);
+            eliminations.extend(outside_eliminations(sudoku, cell, &sets, box_digits));
+            eliminations.sort_unstable();
+            eliminations.dedup();
+            if !eliminations.is_empty() {
+                steps.push(AlsStep {
+                    kind: AlsKind::SueDeCoq,
+                    sets: sets.into_iter().cloned().collect(),
+                    restricted: 0,
+                    eliminations,
+                });
+            }
+        }
+    }
+}
+
+fn unsolved_outside(
+    sudoku: &Sudoku,
+    unit: &[usize; consts::WIDTH],
+    other: &[usize; consts::WIDTH],
+) -> Vec<usize> {
+    unit.iter()
+        .copied()
+        .filter(|idx| !other.contains(idx) && sudoku.digits[*idx] == 0)
+        .collect()
+}
+
+/// Candidates of `digits` in `unit` outside of all `sets`.
+fn outside_eliminations(
+    sudoku: &Sudoku,
+    unit: &[usize; consts::WIDTH],
+    sets: &[&Als],
+    digits: consts::BitWidth,
+) -> Vec<Candidate> {
+    unit.iter()
+        .filter(|idx| !sets.iter().any(|set| set.cells.contains(idx)))
+        .flat_map(|&idx| {
+            candidate::digits(sudoku.bitboard[idx] & digits)
+                .map(move |digit| Candidate::new(idx, digit))
+        })
+        .collect()
+}
+
+/// Digits that appear in both sets, where every occurrence in one set sees
+/// every occurrence in the other.
+fn restricted_commons(sudoku: &Sudoku, a: &Als, b: &Als) -> consts::BitWidth {
+    candidate::digits(a.digits & b.digits)
+        .filter(|&digit| {
+            a.cells_with(sudoku, digit)
+                .all(|x| b.cells_with(sudoku, digit).all(|y| sees(x, y)))
+        })
+        .fold(0, |mask, digit| mask | (1 << digit))
+}
+
+fn single_digit_pairs(
+    x: consts::BitWidth,
+    y: consts::BitWidth,
+) -> impl Iterator<Item = (consts::BitWidth, consts::BitWidth)> {
+    candidate::digits(x).flat_map(move |dx| {
+        candidate::digits(y)
+            .filter(move |&dy| dy != dx)
+            .map(move |dy| (dx, dy))
+    })
+}
+
+/// For each digit in `digits`, the candidates outside of `sets` that see every
+/// occurrence of the digit in all of the sets.
+fn common_digit_eliminations(
+    sudoku: &Sudoku,
+    sets: &[&Als],
+    digits: consts::BitWidth,
+) -> Vec<Candidate> {
+    candidate::digits(digits)
+        .flat_map(|digit| {
+            let pattern: Vec<usize> = sets
+                .iter()
+                .flat_map(|set| set.cells_with(sudoku, digit))
+                .collect();
+            candidate::eliminations_seen_by(sudoku, &pattern, digit)
+                .into_iter()
+                .filter(|candidate| !sets.iter().any(|set| set.cells.contains(&candidate.idx)))
+        })
+        .collect()
+}
+
+/// With two restricted common candidates, each set is locked: every digit of a
+/// set must appear in it.
+fn doubly_linked_eliminations(
+    sudoku: &Sudoku,
+    a: &Als,
+    b: &Als,
+    restricted: consts::BitWidth,
+) -> Vec<Candidate> {
+    let mut eliminations = common_digit_eliminations(sudoku, &[a, b], restricted);
+    eliminations.extend(common_digit_eliminations(
+        sudoku,
+        &[a],
+        a.digits & !restricted,
+    ));
+    eliminations.extend(common_digit_eliminations(
+        sudoku,
+        &[b],
+        b.digits & !restricted,
+    ));
+    eliminations
+        .retain(|candidate| !a.cells.contains(&candidate.idx) && !b.cells.contains(&candidate.idx));
+    eliminations.sort_unstable();
+    eliminations.dedup();
+    eliminations
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::{assert_eliminations_are_sound, with_candidates};
+    use rstest::rstest;
+
+    #[test]
+    fn test_almost_locked_sets() {
+        let sudoku = with_candidates(&[(0, &[1, 2]), (4, &[1, 2, 3]), (13, &[2, 3])]);
+        let sets = find_almost_locked_sets(&sudoku);
+
+        assert!(sets.iter().any(|als| als.cells == vec![0]));
+        assert!(sets.iter().any(|als| als.cells == vec![4, 13]));
+        assert!(sets.iter().all(Als::is_almost_locked));
+    }
+
+    #[test]
+    fn test_als_xz() {
+        // r1c1 {1, 2} and r1c5,r2c5 {1, 2, 3} are linked by 1 in row 1, so one
+        // of them holds 2, and r1c4 and r1c6 see every 2 in both.
+        let sudoku = with_candidates(&[(0, &[1, 2]), (4, &[1, 2, 3]), (13, &[2, 3])]);
+        let a = Als::new(&sudoku, vec![0]);
+        let b = Als::new(&sudoku, vec![4, 13]);
+
+        assert_eq!(restricted_commons(&sudoku, &a, &b), 1 << 1);
+        let steps = find_als_xz(&sudoku, &[a, b]);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(
+            steps[0].eliminations,
+            vec![Candidate::new(3, 2), Candidate::new(5, 2)]
+        );
+        assert_eq!(
+            steps[0].to_string(),
+            "ALS-XZ: r1c1{12} / r1c5,r2c5{123} RCC 1 => r1c4<>2 r1c6<>2"
+        );
+    }
+
+    #[test]
+    fn test_sue_de_coq() {
+        // r1c1,r1c2 {1234} with r1c5 {12} in row 1 and r2c1 {34} in box 1
+        let sudoku = with_candidates(&[
+            (0, &[1, 2, 3, 4]),
+            (1, &[1, 2, 3, 4]),
+            (4, &[1, 2]),
+            (9, &[3, 4]),
+        ]);
+        let steps: Vec<AlsStep> = find_sue_de_coq(&sudoku)
+            .into_iter()
+            .filter(|step| step.sets[0].cells == vec![0, 1])
+            .collect();
+
+        assert_eq!(steps.len(), 1);
+        let step = &steps[0];
+        assert_eq!(step.sets[1].cells, vec![4]);
+        assert_eq!(step.sets[2].cells, vec![9]);
+        // r1c3 lies in both the row and the box, and loses all four digits
+        assert_eq!(step.eliminations.len(), 24);
+        assert!(step.eliminations.iter().all(|c| {
+            let in_row = c.idx < consts::WIDTH && c.digit <= 2;
+            let in_box = consts::CELLS[0].contains(&c.idx) && c.digit >= 3;
+            in_row || in_box
+        }));
+    }
+
+    #[rstest]
+    #[case(
+        "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..",
+        "397514628415628973286937154978362415631845792542791386729456831163289547854173269"
+    )]
+    fn test_eliminations_are_sound(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        assert_eliminations_are_sound(input, expected, find_als_steps, apply_als_step, |step| {
+            &step.eliminations
+        })?;
+        Ok(())
+    }
+}
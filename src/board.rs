@@ -0,0 +1,48 @@
+use crate::{consts::BitWidth, hidden::place_all_hidden_singles, shape::Shape, Result};
+
+/// A puzzle that the techniques and the search can work on, such as a
+/// [`Sudoku`](crate::Sudoku) of any [`Shape`].
+///
+/// Digits run from 1 to the width of the shape, and candidates are masks
+/// with bit `d` set for digit `d`.
+pub trait Board: Clone + 'static {
+    type Shape: Shape;
+
+    fn shape(&self) -> &Self::Shape;
+
+    /// The digit placed in a cell, or 0 if it is empty.
+    fn digit(&self, idx: usize) -> BitWidth;
+
+    /// The candidates left in a cell, which is empty once a digit is placed.
+    fn candidates(&self, idx: usize) -> BitWidth;
+
+    /// Places a digit, and removes it from the candidates of every neighbor.
+    fn place(&mut self, idx: usize, digit: BitWidth);
+
+    /// Removes every candidate of the cell that is not in `mask`.
+    fn keep_candidates(&mut self, idx: usize, mask: BitWidth);
+
+    /// The number of cells with a placed digit.
+    fn num_digits(&self) -> usize;
+
+    /// The number of candidates removed from the board since it was created,
+    /// including the remaining candidates of cells that got a digit placed.
+    fn num_removed(&self) -> u64;
+
+    #[inline]
+    fn is_solved(&self) -> bool {
+        self.num_digits() == self.shape().size()
+    }
+
+    /// Places every digit that has a single position left in a unit, see
+    /// [`place_all_hidden_singles`](crate::place_all_hidden_singles). Boards
+    /// that track the positions of every digit can find them faster.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board is invalid.
+    #[inline]
+    fn place_hidden_singles(&mut self) -> Result<()> {
+        place_all_hidden_singles(self)
+    }
+}
@@ -0,0 +1,283 @@
+use core::fmt;
+
+use crate::{consts, solver::place_and_propagate, sudoku::Sudoku, Error, Result};
+
+/// A single candidate digit in a single cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Candidate {
+    pub idx: usize,
+    pub digit: consts::BitWidth,
+}
+
+/// Formats a cell index as `r<row>c<col>`, both 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellName(pub usize);
+
+impl fmt::Display for CellName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let row = self.0 / consts::WIDTH + 1;
+        let col = self.0 % consts::WIDTH + 1;
+        write!(f, "r{row}c{col}")
+    }
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}<>{}", CellName(self.idx), self.digit)
+    }
+}
+
+impl Candidate {
+    #[must_use]
+    pub const fn new(idx: usize, digit: consts::BitWidth) -> Self {
+        Self { idx, digit }
+    }
+
+    /// Returns true if at most one of the two candidates can be true, either
+    /// because they share a cell or because they are the same digit in cells
+    /// that see each other.
+    #[must_use]
+    pub fn is_weakly_linked(&self, other: &Self) -> bool {
+        if self.idx == other.idx {
+            self.digit != other.digit
+        } else {
+            self.digit == other.digit && sees(self.idx, other.idx)
+        }
+    }
+}
+
+/// Returns true if the two cells share a row, column or box.
+#[inline]
+#[must_use]
+pub fn sees(a: usize, b: usize) -> bool {
+    consts::NEIGHBORS[a].contains(&b)
+}
+
+/// Iterates over the cells in an 81-bit set, in ascending order.
+#[inline]
+pub fn cells(mut set: u128) -> impl Iterator<Item = usize> {
+    core::iter::from_fn(move || {
+        (set != 0).then(|| {
+            let idx = set.trailing_zeros() as usize;
+            set &= set - 1;
+            idx
+        })
+    })
+}
+
+/// Iterates over the digits set in a candidate mask.
+#[inline]
+pub fn digits(mask: consts::BitWidth) -> impl Iterator<Item = consts::BitWidth> {
+    (1..=consts::WIDTH).filter(move |&digit| mask & (1 << digit) > 0)
+}
+
+/// Iterates over all remaining candidates in the Sudoku.
+pub fn candidates(sudoku: &Sudoku) -> impl Iterator<Item = Candidate> + '_ {
+    (0..consts::SIZE).flat_map(move |idx| {
+        digits(sudoku.bitboard[idx]).map(move |digit| Candidate::new(idx, digit))
+    })
+}
+
+/// Iterates over all 27 units (rows, columns and boxes).
+pub fn units() -> impl Iterator<Item = &'static [usize; consts::WIDTH]> {
+    consts::ROWS
+        .iter()
+        .chain(consts::COLS.iter())
+        .chain(consts::CELLS.iter())
+}
+
+/// Returns the cells in a unit that still have `digit` as a candidate.
+pub fn cells_with_digit<'a>(
+    sudoku: &'a Sudoku,
+    unit: &'a [usize; consts::WIDTH],
+    digit: consts::BitWidth,
+) -> impl Iterator<Item = usize> + 'a {
+    unit.iter()
+        .copied()
+        .filter(move |&idx| sudoku.bitboard[idx] & (1 << digit) > 0)
+}
+
+/// Returns all strong links for `digit`: pairs of cells that are the only two
+/// candidates for the digit within some unit.
+#[cfg(feature = "std")]
+#[must_use]
+pub fn strong_links(sudoku: &Sudoku, digit: consts::BitWidth) -> Vec<[usize; 2]> {
+    let mut links: Vec<[usize; 2]> = units()
+        .filter_map(|unit| {
+            let mut cells = cells_with_digit(sudoku, unit, digit);
+            match (cells.next(), cells.next(), cells.next()) {
+                (Some(a), Some(b), None) => Some([a, b]),
+                _ => None,
+            }
+        })
+        .collect();
+    links.sort_unstable();
+    links.dedup();
+    links
+}
+
+/// Returns the unsolved cells outside of `pattern` that have `digit` as a
+/// candidate and see every cell in `pattern`.
+#[cfg(feature = "std")]
+#[must_use]
+pub fn eliminations_seen_by(
+    sudoku: &Sudoku,
+    pattern: &[usize],
+    digit: consts::BitWidth,
+) -> Vec<Candidate> {
+    (0..consts::SIZE)
+        .filter(|&idx| sudoku.bitboard[idx] & (1 << digit) > 0)
+        .filter(|idx| !pattern.contains(idx))
+        .filter(|&idx| pattern.iter().all(|&cell| sees(idx, cell)))
+        .map(|idx| Candidate::new(idx, digit))
+        .collect()
+}
+
+/// All non-empty subsets of `cells` with at most `max_len` elements.
+#[cfg(feature = "std")]
+#[must_use]
+pub fn subsets(cells: &[usize], max_len: usize) -> Vec<Vec<usize>> {
+    let mut result: Vec<Vec<usize>> = vec![vec![]];
+    for &idx in cells {
+        let extended: Vec<Vec<usize>> = result
+            .iter()
+            .filter(|subset| subset.len() < max_len)
+            .map(|subset| subset.iter().copied().chain([idx]).collect())
+            .collect();
+        result.extend(extended);
+    }
+    result.retain(|subset| !subset.is_empty());
+    result
+}
+
+/// Removes all given candidates from the Sudoku, placing any cell that is
+/// reduced to a single candidate.
+///
+/// # Errors
+///
+/// Returns an error if a cell is left without candidates.
+pub fn eliminate(sudoku: &mut Sudoku, eliminations: &[Candidate]) -> Result<()> {
+    eliminations
+        .iter()
+        .try_for_each(|candidate| eliminate_candidate(sudoku, *candidate))
+}
+
+fn eliminate_candidate(sudoku: &mut Sudoku, candidate: Candidate) -> Result<()> {
+    let idx = candidate.idx;
+    sudoku.keep_candidates(idx, consts::MASK ^ (1 << candidate.digit));
+    if sudoku.digits[idx] == 0 && sudoku.bitboard[idx] == 0 {
+        Err(Error::NoSolution)
+    } else if sudoku.bitboard[idx].is_power_of_two() {
+        let digit = sudoku.bitboard[idx].trailing_zeros() as consts::BitWidth;
+        place_and_propagate(sudoku, idx, digit)
+    } else {
+        Ok(())
+    }
+}
+
+/// Helpers shared by the tests of the solving techniques.
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+pub mod fixtures {
+    use core::str::FromStr;
+
+    use super::Candidate;
+    use crate::{consts, solver::check_constraints, sudoku::Sudoku, Result};
+
+    /// Returns an empty Sudoku where the given cells only have the given
+    /// candidates left.
+    pub fn with_candidates(cells: &[(usize, &[consts::BitWidth])]) -> Sudoku {
+        let mut sudoku = Sudoku::default();
+        for &(idx, digits) in cells {
+            sudoku.bitboard[idx] = digits.iter().fold(0, |mask, digit| mask | (1 << digit));
+        }
+        sudoku
+    }
+
+    /// Returns an empty Sudoku where `digit` is only a candidate in `cells`.
+    pub fn with_digit_in(cells: &[usize], digit: consts::BitWidth) -> Sudoku {
+        let mut sudoku = Sudoku::default();
+        for idx in 0..consts::SIZE {
+            if !cells.contains(&idx) {
+                sudoku.bitboard[idx] &= consts::MASK ^ (1 << digit);
+            }
+        }
+        sudoku
+    }
+
+    /// Applies a technique to `input` until it stops making progress, and
+    /// checks that it finds something and never eliminates a digit of the
+    /// solution `expected`. Returns the Sudoku it ends up with.
+    pub fn assert_eliminations_are_sound<T>(
+        input: &str,
+        expected: &str,
+        find: impl Fn(&Sudoku) -> Vec<T>,
+        apply: impl Fn(&mut Sudoku) -> Result<Option<T>>,
+        eliminations: impl Fn(&T) -> &[Candidate],
+    ) -> Result<Sudoku> {
+        let mut sudoku = Sudoku::from_str(input)?;
+        let solution = Sudoku::from_str(expected)?;
+        check_constraints(&mut sudoku)?;
+        check_constraints(&mut sudoku)?;
+        assert!(!find(&sudoku).is_empty());
+
+        while let Some(step) = apply(&mut sudoku)? {
+            assert!(eliminations(&step)
+                .iter()
+                .all(|c| solution.digits[c.idx] != c.digit));
+        }
+        Ok(sudoku)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use rstest::rstest;
+    #[cfg(feature = "std")]
+    use std::str::FromStr;
+
+    #[rstest]
+    #[case(0, 8, true)]
+    #[case(0, 72, true)]
+    #[case(0, 20, true)]
+    #[case(0, 21, false)]
+    #[case(40, 80, false)]
+    fn test_sees(#[case] a: usize, #[case] b: usize, #[case] expected: bool) {
+        assert_eq!(sees(a, b), expected);
+    }
+
+    #[rstest]
+    #[case(0, 0, "r1c1")]
+    #[case(80, 9, "r9c9<>9")]
+    #[case(10, 3, "r2c2<>3")]
+    fn test_display(#[case] idx: usize, #[case] digit: consts::BitWidth, #[case] expected: &str) {
+        let name = if digit == 0 {
+            CellName(idx).to_string()
+        } else {
+            Candidate::new(idx, digit).to_string()
+        };
+        assert_eq!(name, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_subsets() {
+        assert_eq!(subsets(&[1, 2, 3], 2).len(), 6);
+        assert_eq!(subsets(&[1, 2, 3], 3).len(), 7);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_strong_links() -> Result<()> {
+        let sudoku = Sudoku::from_str(
+            "............1...........1..1.....................................................",
+        )?;
+        let links = strong_links(&sudoku, 1);
+        assert!(links.contains(&[1, 2]));
+        assert!(!links.iter().any(|link| link.contains(&0)));
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+use std::{collections::VecDeque, fmt};
+
+use crate::{
+    candidate::{self, sees, strong_links, Candidate, CellName},
+    consts,
+    sudoku::Sudoku,
+    Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// At least one of the two candidates is true.
+    Strong,
+    /// At most one of the two candidates is true.
+    Weak,
+}
+
+/// A chain of candidates, where `links[i]` connects `nodes[i]` and
+/// `nodes[i + 1]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    pub nodes: Vec<Candidate>,
+    pub links: Vec<LinkKind>,
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                match self.links[i - 1] {
+                    LinkKind::Strong => write!(f, "=")?,
+                    LinkKind::Weak => write!(f, "-")?,
+                }
+            }
+            write!(f, "({}){}", node.digit, CellName(node.idx))?;
+        }
+        Ok(())
+    }
+}
+
+impl Chain {
+    #[must_use]
+    pub fn first(&self) -> Option<&Candidate> {
+        self.nodes.first()
+    }
+
+    #[must_use]
+    pub fn last(&self) -> Option<&Candidate> {
+        self.nodes.last()
+    }
+
+    /// Returns true if `idx` is the cell of any node in the chain.
+    #[must_use]
+    pub fn contains_cell(&self, idx: usize) -> bool {
+        self.nodes.iter().any(|node| node.idx == idx)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XChainKind {
+    /// Two parallel conjugate pairs with one end of each sharing a line.
+    Skyscraper,
+    /// A row and a column conjugate pair with one end of each sharing a box.
+    TwoStringKite,
+    /// Any other chain of two conjugate pairs joined by a weak link.
+    TurbotFish,
+    XChain,
+}
+
+impl fmt::Display for XChainKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Skyscraper => write!(f, "Skyscraper"),
+            Self::TwoStringKite => write!(f, "2-String Kite"),
+            Self::TurbotFish => write!(f, "Turbot Fish"),
+            Self::XChain => write!(f, "X-Chain"),
+        }
+    }
+}
+
+/// An alternating chain on a single digit that starts and ends with a strong
+/// link, so one of its ends must be true.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XChain {
+    pub kind: XChainKind,
+    pub digit: consts::BitWidth,
+    pub chain: Chain,
+    pub eliminations: Vec<Candidate>,
+}
+
+impl fmt::Display for XChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} =>", self.kind, self.chain)?;
+        self.eliminations
+            .iter()
+            .try_for_each(|candidate| write!(f, " {candidate}"))
+    }
+}
+
+/// Find all X-Chains, including Skyscrapers, 2-String Kites and Turbot Fish,
+/// that eliminate at least one candidate. Only the shortest chain between two
+/// ends is reported.
+#[must_use]
+pub fn find_x_chains(sudoku: &Sudoku) -> Vec<XChain> {
+    (1..=consts::WIDTH)
+        .flat_map(|digit| find_x_chains_for_digit(sudoku, digit))
+        .collect()
+}
+
+/// Apply the eliminations of the shortest X-Chain found, if any.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn apply_x_chain(sudoku: &mut Sudoku) -> Result<Option<XChain>> {
+    let Some(x_chain) = find_x_chains(sudoku)
+        .into_iter()
+        .min_by_key(|x_chain| x_chain.chain.links.len())
+    else {
+        return Ok(None);
+    };
+    candidate::eliminate(sudoku, &x_chain.eliminations)?;
+    Ok(Some(x_chain))
+}
+
+/// Returns the candidates outside of the chain that are weakly linked to both
+/// of its ends. As one of the ends must be true, these are all false.
+#[must_use]
+pub fn chain_eliminations(sudoku: &Sudoku, chain: &Chain) -> Vec<Candidate> {
+    let (Some(first), Some(last)) = (chain.first(), chain.last()) else {
+        return vec![];
+    };
+    candidate::candidates(sudoku)
+        .filter(|candidate| !chain.nodes.contains(candidate))
+        .filter(|candidate| candidate.is_weakly_linked(first) && candidate.is_weakly_linked(last))
+        .collect()
+}
+
+fn find_x_chains_for_digit(sudoku: &Sudoku, digit: consts::BitWidth) -> Vec<XChain> {
+    let links = strong_links(sudoku, digit);
+    let cells: Vec<usize> = (0..consts::SIZE)
+        .filter(|&idx| sudoku.bitboard[idx] & (1 << digit) > 0)
+        .collect();
+
+    let mut x_chains = vec![];
+    for &start in &cells {
+        for path in shortest_paths(&links, &cells, start) {
+            let chain = Chain {
+                links: (1..path.len()).map(|i| link_kind(i - 1)).collect(),
+                nodes: path.iter().map(|&idx| Candidate::new(idx, digit)).collect(),
+            };
+            let eliminations = chain_eliminations(sudoku, &chain);
+            if !eliminations.is_empty() {
+                x_chains.push(XChain {
+                    kind: classify(&path),
+                    digit,
+                    chain,
+                    eliminations,
+                });
+            }
+        }
+    }
+    x_chains
+}
+
+/// The kind of the `i`th link of a chain that starts with a strong link.
+pub const fn link_kind(i: usize) -> LinkKind {
+    if i.is_multiple_of(2) {
+        LinkKind::Strong
+    } else {
+        LinkKind::Weak
+    }
+}
+
+/// Breadth first search over (cell, parity) states, where an odd parity means
+/// the cell was reached through a strong link. Returns the shortest path to
+/// every cell after `start` that ends on a strong link and spans at least
+/// three links.
+fn shortest_paths(links: &[[usize; 2]], cells: &[usize], start: usize) -> Vec<Vec<usize>> {
+    let mut parent: [Option<usize>; 2 * consts::SIZE] = [None; 2 * consts::SIZE];
+    let mut visited = [false; 2 * consts::SIZE];
+    let mut queue = VecDeque::from([2 * start]);
+    visited[2 * start] = true;
+    let mut paths = vec![];
+
+    while let Some(state) = queue.pop_front() {
+        let (idx, parity) = (state / 2, state % 2);
+        let next_states: Vec<usize> = if parity == 0 {
+            links
+                .iter()
+                .filter_map(|&[a, b]| match idx {
+                    _ if idx == a => Some(b),
+                    _ if idx == b => Some(a),
+                    _ => None,
+                })
+                .map(|next| 2 * next + 1)
+                .collect()
+        } else {
+            cells
+                .iter()
+                .filter(|&&next| sees(idx, next))
+                .map(|&next| 2 * next)
+                .collect()
+        };
+        for next in next_states {
+            if visited[next] {
+                continue;
+            }
+            visited[next] = true;
+            parent[next] = Some(state);
+            queue.push_back(next);
+            if next % 2 == 1 && next / 2 > start {
+                let path = reconstruct(&parent, next);
+                if path.len() >= 4 && !has_repeated_cell(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+    }
+    paths
+}
+
+fn reconstruct(parent: &[Option<usize>], end: usize) -> Vec<usize> {
+    let mut path = vec![end / 2];
+    let mut state = end;
+    while let Some(previous) = parent[state] {
+        path.push(previous / 2);
+        state = previous;
+    }
+    path.reverse();
+    path
+}
+
+fn has_repeated_cell(path: &[usize]) -> bool {
+    path.iter()
+        .enumerate()
+        .any(|(i, idx)| path[i + 1..].contains(idx))
+}
+
+const fn same_row(a: usize, b: usize) -> bool {
+    a / consts::WIDTH == b / consts::WIDTH
+}
+
+const fn same_col(a: usize, b: usize) -> bool {
+    a % consts::WIDTH == b % consts::WIDTH
+}
+
+fn classify(path: &[usize]) -> XChainKind {
+    let &[a, b, c, d] = path else {
+        return XChainKind::XChain;
+    };
+    let both_rows = same_row(a, b) && same_row(c, d);
+    let both_cols = same_col(a, b) && same_col(c, d);
+    let row_and_col = (same_row(a, b) && same_col(c, d)) || (same_col(a, b) && same_row(c, d));
+    let same_box = consts::CELL_LOOKUP[b] == consts::CELL_LOOKUP[c];
+
+    if (both_rows && same_col(b, c)) || (both_cols && same_row(b, c)) {
+        XChainKind::Skyscraper
+    } else if row_and_col && same_box {
+        XChainKind::TwoStringKite
+    } else {
+        XChainKind::TurbotFish
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::{assert_eliminations_are_sound, with_digit_in};
+    use rstest::rstest;
+
+    #[rstest]
+    // conjugate pairs in rows 1 and 5, with r1c1 and r5c1 sharing column 1
+    #[case(&[0, 6, 16, 25, 33, 36, 43, 51, 63, 69, 79], XChainKind::Skyscraper)]
+    // a conjugate pair in row 1 and one in column 2, joined in box 1
+    #[case(&[0, 6, 10, 20, 64, 69, 71, 78], XChainKind::TwoStringKite)]
+    fn test_x_chain_kind(#[case] cells: &[usize], #[case] expected: XChainKind) {
+        let sudoku = with_digit_in(cells, 1);
+        let x_chains = find_x_chains(&sudoku);
+
+        assert!(x_chains.iter().any(|x_chain| x_chain.kind == expected));
+    }
+
+    #[test]
+    fn test_display() {
+        let chain = Chain {
+            nodes: vec![
+                Candidate::new(0, 1),
+                Candidate::new(6, 1),
+                Candidate::new(42, 1),
+            ],
+            links: vec![LinkKind::Strong, LinkKind::Weak],
+        };
+        assert_eq!(chain.to_string(), "(1)r1c1=(1)r1c7-(1)r5c7");
+    }
+
+    #[rstest]
+    #[case(
+        "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..",
+        "397514628415628973286937154978362415631845792542791386729456831163289547854173269"
+    )]
+    #[case(
+        ".8..3..9....6.813.62.......2.5.7..19...3..........9..4...8..72116..........2.3...",
+        "481532697597648132623791548235476819914385276876129354359864721162957483748213965"
+    )]
+    fn test_eliminations_are_sound(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        assert_eliminations_are_sound(input, expected, find_x_chains, apply_x_chain, |x_chain| {
+            &x_chain.eliminations
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+
+use crate::{
+    candidate::{self, sees, strong_links, Candidate, CellName},
+    consts,
+    sudoku::Sudoku,
+    Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColoringKind {
+    /// An uncolored candidate sees both colors, so it can never be true.
+    Trap,
+    /// Two candidates of the same color see each other, so that color is false.
+    Wrap,
+}
+
+impl fmt::Display for ColoringKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Trap => write!(f, "Color Trap"),
+            Self::Wrap => write!(f, "Color Wrap"),
+        }
+    }
+}
+
+/// A two-colored cluster of conjugate pairs for a single digit, together with
+/// the candidates it eliminates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coloring {
+    pub kind: ColoringKind,
+    pub digit: consts::BitWidth,
+    pub colors: [Vec<usize>; 2],
+    pub eliminations: Vec<Candidate>,
+}
+
+impl fmt::Display for Coloring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} on {}:", self.kind, self.digit)?;
+        for (color, cells) in ["+", "-"].iter().zip(self.colors.iter()) {
+            write!(f, " {color}")?;
+            for (i, &idx) in cells.iter().enumerate() {
+                let separator = if i == 0 { "" } else { "," };
+                write!(f, "{separator}{}", CellName(idx))?;
+            }
+        }
+        write!(f, " =>")?;
+        self.eliminations
+            .iter()
+            .try_for_each(|candidate| write!(f, " {candidate}"))
+    }
+}
+
+/// Find all color traps and color wraps.
+#[must_use]
+pub fn find_colorings(sudoku: &Sudoku) -> Vec<Coloring> {
+    (1..=consts::WIDTH)
+        .flat_map(|digit| find_colorings_for_digit(sudoku, digit))
+        .collect()
+}
+
+/// Apply the eliminations of the first coloring found, if any.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn apply_coloring(sudoku: &mut Sudoku) -> Result<Option<Coloring>> {
+    let Some(coloring) = find_colorings(sudoku).into_iter().next() else {
+        return Ok(None);
+    };
+    candidate::eliminate(sudoku, &coloring.eliminations)?;
+    Ok(Some(coloring))
+}
+
+fn find_colorings_for_digit(sudoku: &Sudoku, digit: consts::BitWidth) -> Vec<Coloring> {
+    let links = strong_links(sudoku, digit);
+    let mut colored = [false; consts::SIZE];
+    let mut colorings = vec![];
+
+    for &[start, _] in &links {
+        if colored[start] {
+            continue;
+        }
+        let colors = color_cluster(&links, start);
+        for &idx in colors.iter().flatten() {
+            colored[idx] = true;
+        }
+        if colors[0].len() + colors[1].len() < 3 {
+            // a single conjugate pair only repeats locked candidates
+            continue;
+        }
+        if let Some(coloring) = find_wrap(sudoku, digit, &colors) {
+            colorings.push(coloring);
+        } else if let Some(coloring) = find_trap(sudoku, digit, &colors) {
+            colorings.push(coloring);
+        }
+    }
+    colorings
+}
+
+fn color_cluster(links: &[[usize; 2]], start: usize) -> [Vec<usize>; 2] {
+    let mut color = [None; consts::SIZE];
+    let mut queue = vec![start];
+    color[start] = Some(0);
+
+    while let Some(idx) = queue.pop() {
+        let Some(current) = color[idx] else {
+            continue;
+        };
+        for &[a, b] in links {
+            let other = match idx {
+                _ if idx == a => b,
+                _ if idx == b => a,
+                _ => continue,
+            };
+            if color[other].is_none() {
+                color[other] = Some(1 - current);
+                queue.push(other);
+            }
+        }
+    }
+
+    let cells_with_color = |c: usize| (0..consts::SIZE).filter(|&i| color[i] == Some(c)).collect();
+    [cells_with_color(0), cells_with_color(1)]
+}
+
+fn find_wrap(
+    sudoku: &Sudoku,
+    digit: consts::BitWidth,
+    colors: &[Vec<usize>; 2],
+) -> Option<Coloring> {
+    let false_color = colors.iter().position(|cells| {
+        cells
+            .iter()
+            .enumerate()
+            .any(|(i, &a)| cells[i + 1..].iter().any(|&b| sees(a, b)))
+    })?;
+    let eliminations = colors[false_color]
+        .iter()
+        .filter(|&&idx| sudoku.bitboard[idx] & (1 << digit) > 0)
+        .map(|&idx| Candidate::new(idx, digit))
+        .collect();
+    Some(Coloring {
+        kind: ColoringKind::Wrap,
+        digit,
+        colors: colors.clone(),
+        eliminations,
+    })
+}
+
+fn find_trap(
+    sudoku: &Sudoku,
+    digit: consts::BitWidth,
+    colors: &[Vec<usize>; 2],
+) -> Option<Coloring> {
+    let sees_color = |idx: usize, cells: &[usize]| cells.iter().any(|&cell| sees(idx, cell));
+    let eliminations: Vec<Candidate> = (0..consts::SIZE)
+        .filter(|&idx| sudoku.bitboard[idx] & (1 << digit) > 0)
+        .filter(|idx| !colors[0].contains(idx) && !colors[1].contains(idx))
+        .filter(|&idx| sees_color(idx, &colors[0]) && sees_color(idx, &colors[1]))
+        .map(|idx| Candidate::new(idx, digit))
+        .collect();
+    if eliminations.is_empty() {
+        None
+    } else {
+        Some(Coloring {
+            kind: ColoringKind::Trap,
+            digit,
+            colors: colors.clone(),
+            eliminations,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::{assert_eliminations_are_sound, with_digit_in};
+    use rstest::rstest;
+
+    #[test]
+    fn test_color_trap() {
+        // r1c1 = r1c5 = r5c5 = r5c1 is colored +, -, +, -, and r4c1 sees both
+        // r1c1 and r5c1.
+        let sudoku = with_digit_in(&[0, 4, 27, 28, 36, 40], 1);
+        let colorings = find_colorings(&sudoku);
+
+        assert_eq!(colorings.len(), 1);
+        assert_eq!(colorings[0].kind, ColoringKind::Trap);
+        assert_eq!(colorings[0].colors, [vec![0, 40], vec![4, 36]]);
+        assert_eq!(colorings[0].eliminations, vec![Candidate::new(27, 1)]);
+    }
+
+    #[test]
+    fn test_color_wrap() {
+        // r3c1 = r1c1 = r1c5 = r5c5 = r5c1 puts r3c1 and r5c1 in the same
+        // color, but they share column 1.
+        let sudoku = with_digit_in(&[0, 4, 18, 25, 26, 36, 40], 1);
+        let colorings = find_colorings(&sudoku);
+
+        assert_eq!(colorings.len(), 1);
+        assert_eq!(colorings[0].kind, ColoringKind::Wrap);
+        let eliminated: Vec<usize> = colorings[0].eliminations.iter().map(|c| c.idx).collect();
+        assert_eq!(eliminated, vec![4, 18, 36]);
+    }
+
+    #[rstest]
+    #[case(
+        "...6..7..64.7.1.5...5..8..12...........5..2.71..8.4...5.......8...46.3...31...4..",
+        "318652749649731852725948631256397184483516297197824563564173928872469315931285476"
+    )]
+    #[case(
+        "...6..1..7....4...3.....2.7.9...3.2....47.9.3........1.38.6..5.2.......9..7..5...",
+        "589627134726134895341859267894513726152476983673982541938761452215348679467295318"
+    )]
+    fn test_eliminations_are_sound(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        assert_eliminations_are_sound(
+            input,
+            expected,
+            find_colorings,
+            apply_coloring,
+            |coloring| &coloring.eliminations,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    board::Board,
+    candidate::cells,
+    consts,
+    shape::{Boxes, Classic},
+    solver::place_and_propagate,
+    sudoku::{digit_char, Sudoku},
+    Error, Result,
+};
+
+/// A candidate mask using bits 1 through 9, like [`Sudoku::bitboard`].
+pub type Mask = u16;
+
+const MASK: Mask = 0b11_1111_1110;
+const NUM_UNITS: usize = 27;
+const ALL_CELLS: u128 = (1 << consts::SIZE) - 1;
+
+/// Every unit as a set of cells: rows, then columns, then boxes.
+const UNITS: [u128; NUM_UNITS] = unit_sets();
+/// The cells that share a unit with each cell.
+const PEERS: [u128; consts::SIZE] = peer_sets();
+
+const fn unit_sets() -> [u128; NUM_UNITS] {
+    let mut sets = [0; NUM_UNITS];
+    let mut unit = 0;
+    while unit < consts::WIDTH {
+        let mut i = 0;
+        while i < consts::WIDTH {
+            sets[unit] |= 1 << consts::ROWS[unit][i];
+            sets[consts::WIDTH + unit] |= 1 << consts::COLS[unit][i];
+            sets[2 * consts::WIDTH + unit] |= 1 << consts::CELLS[unit][i];
+            i += 1;
+        }
+        unit += 1;
+    }
+    sets
+}
+
+const fn peer_sets() -> [u128; consts::SIZE] {
+    let mut sets = [0; consts::SIZE];
+    let mut idx = 0;
+    while idx < consts::SIZE {
+        let mut i = 0;
+        while i < consts::NEIGHBORS[idx].len() {
+            sets[idx] |= 1 << consts::NEIGHBORS[idx][i];
+            i += 1;
+        }
+        idx += 1;
+    }
+    sets
+}
+
+/// The row, column and box of a cell, as indices into `UNITS`.
+const fn units_of(idx: usize) -> [usize; 3] {
+    [
+        idx / consts::WIDTH,
+        consts::WIDTH + idx % consts::WIDTH,
+        2 * consts::WIDTH + consts::CELL_LOOKUP[idx],
+    ]
+}
+
+/// A Sudoku stored as `u16` candidate masks, along with the digits used in
+/// every unit and the cells where every digit is still a candidate.
+///
+/// It is solved by the same techniques and search as [`Sudoku`]. Placements
+/// only touch the neighbors that actually lose a candidate, and hidden singles
+/// become bitwise operations on the position sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactSudoku {
+    candidates: [Mask; consts::SIZE],
+    digits: [u8; consts::SIZE],
+    /// The digits placed in each unit.
+    used: [Mask; NUM_UNITS],
+    /// For each digit, the cells where it is still a candidate.
+    positions: [u128; consts::WIDTH + 1],
+    num_digits: usize,
+    num_removed: u64,
+}
+
+impl Default for CompactSudoku {
+    fn default() -> Self {
+        let mut positions = [ALL_CELLS; consts::WIDTH + 1];
+        positions[0] = 0;
+        Self {
+            candidates: [MASK; consts::SIZE],
+            digits: [0; consts::SIZE],
+            used: [0; NUM_UNITS],
+            positions,
+            num_digits: 0,
+            num_removed: 0,
+        }
+    }
+}
+
+impl fmt::Display for CompactSudoku {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s: String = self
+            .digits
+            .iter()
+            .map(|&digit| digit_char(consts::BitWidth::from(digit)))
+            .collect();
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for CompactSudoku {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Sudoku::from_str(s).map(|sudoku| Self::from(&sudoku))
+    }
+}
+
+impl From<&Sudoku> for CompactSudoku {
+    fn from(sudoku: &Sudoku) -> Self {
+        let mut compact = Self::default();
+        for idx in (0..consts::SIZE).filter(|&idx| sudoku.digits[idx] != 0) {
+            compact.place(idx, sudoku.digits[idx]);
+        }
+        for idx in (0..consts::SIZE).filter(|&idx| sudoku.digits[idx] == 0) {
+            compact.keep_candidates(idx, sudoku.bitboard[idx]);
+        }
+        compact
+    }
+}
+
+impl From<&CompactSudoku> for Sudoku {
+    fn from(compact: &CompactSudoku) -> Self {
+        let mut sudoku = Self::default();
+        for idx in (0..consts::SIZE).filter(|&idx| compact.digits[idx] != 0) {
+            sudoku.place(idx, consts::BitWidth::from(compact.digits[idx]));
+        }
+        for idx in (0..consts::SIZE).filter(|&idx| compact.digits[idx] == 0) {
+            sudoku.keep_candidates(idx, consts::BitWidth::from(compact.candidates[idx]));
+        }
+        sudoku
+    }
+}
+
+impl CompactSudoku {
+    /// Every cell that is the only place left for a digit in one of its
+    /// units.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a digit has no place left in a unit.
+    pub fn hidden_singles(&self) -> Result<Vec<(usize, u8)>> {
+        let mut singles = Vec::new();
+        for (unit, &set) in UNITS.iter().enumerate() {
+            for digit in 1..=consts::WIDTH as u8 {
+                if self.used[unit] & (1 << digit) != 0 {
+                    continue;
+                }
+                let places = self.positions[usize::from(digit)] & set;
+                if places == 0 {
+                    return Err(Error::NoSolution);
+                } else if places.is_power_of_two() {
+                    singles.push((places.trailing_zeros() as usize, digit));
+                }
+            }
+        }
+        Ok(singles)
+    }
+}
+
+impl Board for CompactSudoku {
+    type Shape = Classic;
+
+    #[inline]
+    fn shape(&self) -> &Classic {
+        &Boxes
+    }
+
+    #[inline]
+    fn digit(&self, idx: usize) -> consts::BitWidth {
+        consts::BitWidth::from(self.digits[idx])
+    }
+
+    #[inline]
+    fn candidates(&self, idx: usize) -> consts::BitWidth {
+        consts::BitWidth::from(self.candidates[idx])
+    }
+
+    #[inline]
+    fn place(&mut self, idx: usize, digit: consts::BitWidth) {
+        let cell = 1 << idx;
+        for candidate in 1..=consts::WIDTH {
+            if self.candidates[idx] & (1 << candidate) != 0 {
+                self.positions[candidate] &= !cell;
+            }
+        }
+        self.num_removed += u64::from(self.candidates[idx].count_ones());
+        self.candidates[idx] = 0;
+        self.digits[idx] = digit as u8;
+        self.num_digits += 1;
+
+        let bit = 1 << digit;
+        for neighbor in cells(self.positions[digit] & PEERS[idx]) {
+            self.candidates[neighbor] &= !bit;
+            self.num_removed += 1;
+        }
+        self.positions[digit] &= !PEERS[idx];
+        for unit in units_of(idx) {
+            self.used[unit] |= bit;
+        }
+    }
+
+    #[inline]
+    fn keep_candidates(&mut self, idx: usize, mask: consts::BitWidth) {
+        let removed = self.candidates[idx] & !(mask as Mask);
+        for digit in cells(u128::from(removed)) {
+            self.positions[digit] &= !(1 << idx);
+        }
+        self.num_removed += u64::from(removed.count_ones());
+        self.candidates[idx] ^= removed;
+    }
+
+    #[inline]
+    fn num_digits(&self) -> usize {
+        self.num_digits
+    }
+
+    #[inline]
+    fn num_removed(&self) -> u64 {
+        self.num_removed
+    }
+
+    fn place_hidden_singles(&mut self) -> Result<()> {
+        for (idx, digit) in self.hidden_singles()? {
+            if self.digits[idx] == digit {
+                continue;
+            }
+            if self.candidates[idx] & (1 << digit) == 0 {
+                return Err(Error::NoSolution);
+            }
+            place_and_propagate(self, idx, consts::BitWidth::from(digit))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::solver;
+    use rstest::rstest;
+
+    #[test]
+    fn test_place() {
+        let mut sudoku = CompactSudoku::default();
+        sudoku.place(10, 5);
+
+        assert_eq!(sudoku.digit(10), 5);
+        assert_eq!(sudoku.candidates(10), 0);
+        assert_eq!(sudoku.candidates(11) & (1 << 5), 0);
+        assert_eq!(sudoku.candidates(80), consts::MASK);
+        for unit in units_of(10) {
+            assert_eq!(sudoku.used[unit], 1 << 5);
+        }
+        assert_eq!(sudoku.positions[5] & (PEERS[10] | 1 << 10), 0);
+        assert_eq!(sudoku.positions[4] & (1 << 10), 0);
+        assert_eq!(sudoku.positions[4] & PEERS[10], PEERS[10]);
+    }
+
+    #[test]
+    fn test_hidden_singles() -> Result<()> {
+        // 7 only fits r5c5 in the middle box
+        let mut sudoku = CompactSudoku::default();
+        for idx in consts::CELLS[4].into_iter().filter(|&idx| idx != 40) {
+            sudoku.keep_candidates(idx, !(1 << 7));
+        }
+
+        assert_eq!(sudoku.hidden_singles()?, vec![(40, 7)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut sudoku = Sudoku::from_str(
+            "000000036030000050200000000000060800700000400000053000000700210060900000001000000",
+        )?;
+        crate::solver::check_constraints(&mut sudoku)?;
+        let compact = CompactSudoku::from(&sudoku);
+        let back = Sudoku::from(&compact);
+
+        assert_eq!(back.to_string(), sudoku.to_string());
+        assert_eq!(back.bitboard, sudoku.bitboard);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("057000300000801000001000000600030090020070000800000000400600000000000207000000050")]
+    #[case("000000036030000050200000000000060800700000400000053000000700210060900000001000000")]
+    fn test_solve(#[case] input: &str) -> Result<()> {
+        let (solution, stats) = solver::solve(CompactSudoku::from_str(input)?)?;
+        let (expected, _) = solver::solve(Sudoku::from_str(input)?)?;
+
+        assert_eq!(solution.to_string(), expected.to_string());
+        assert!(stats.hidden_singles.placements > 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use crate::{
+    board::Board, consts::BitWidth, geometry::Geometry, sudoku::Sudoku, technique::Progress, Error,
+    Result,
+};
+
+/// A candidate mask using bits 1 through the width of the grid.
+pub type Mask = u32;
+
+/// Iterates over the digits set in a candidate mask.
+pub fn digits(mut mask: Mask) -> impl Iterator<Item = u8> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let digit = mask.trailing_zeros() as u8;
+            mask &= mask - 1;
+            digit
+        })
+    })
+}
+
+/// A rule on top of the units of a board, such as a killer cage.
+///
+/// A boxed constraint is a [`Technique`](crate::Technique), so constraints are
+/// added to a [`Pipeline`](crate::Pipeline) and run after the singles,
+/// pointing and doubles on every pass of the search.
+pub trait Constraint<B = Sudoku>: fmt::Debug + Send + Sync {
+    /// Removes the candidates that can't be part of a solution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the constraint can no longer be satisfied.
+    fn propagate(&self, board: &mut B) -> Result<Progress>;
+}
+
+/// Parses a cell written as `r<row>c<col>`, both 1-based.
+///
+/// # Errors
+///
+/// Returns an error if the cell is malformed or outside of the grid.
+pub fn parse_cell(geometry: &Geometry, cell: &str) -> Result<usize> {
+    let (row, col) = cell
+        .strip_prefix(['r', 'R'])
+        .and_then(|rest| rest.split_once(['c', 'C']))
+        .ok_or(Error::ParseError)?;
+    let row: usize = row.parse().ok().ok_or(Error::ParseError)?;
+    let col: usize = col.parse().ok().ok_or(Error::ParseError)?;
+    if !(1..=geometry.width()).contains(&row) || !(1..=geometry.width()).contains(&col) {
+        return Err(Error::IndexError);
+    }
+    Ok((row - 1) * geometry.width() + col - 1)
+}
+
+/// The digits that a cell can still hold: its placed digit, or its
+/// candidates if it is empty.
+#[must_use]
+pub fn options<B: Board>(board: &B, idx: usize) -> Mask {
+    match board.digit(idx) {
+        0 => board.candidates(idx) as Mask,
+        digit => 1 << digit,
+    }
+}
+
+/// Removes the candidates of a cell that are not in `allowed`.
+///
+/// # Errors
+///
+/// Returns an error if the cell can't hold any digit in `allowed`.
+pub fn restrict<B: Board>(board: &mut B, idx: usize, allowed: Mask) -> Result<Progress> {
+    if options(board, idx) & allowed == 0 {
+        return Err(Error::NoSolution);
+    }
+    let num_removed = board.num_removed();
+    board.keep_candidates(idx, allowed as BitWidth);
+    Ok((board.num_removed() != num_removed).into())
+}
+
+/// The digits in `to` that are related to at least one digit in `from`.
+pub fn supported(from: Mask, to: Mask, related: impl Fn(u8, u8) -> bool) -> Mask {
+    digits(to)
+        .filter(|&b| digits(from).any(|a| related(a, b)))
+        .fold(0, |mask, digit| mask | 1 << digit)
+}
@@ -250,174 +250,6 @@ pub const NEIGHBORS: [[usize; 20]; SIZE] = [
     ],
 ];
 
-pub const SAME_ROW: [[usize; 8]; SIZE] = [
-    [1, 2, 3, 4, 5, 6, 7, 8],
-    [0, 2, 3, 4, 5, 6, 7, 8],
-    [0, 1, 3, 4, 5, 6, 7, 8],
-    [0, 1, 2, 4, 5, 6, 7, 8],
-    [0, 1, 2, 3, 5, 6, 7, 8],
-    [0, 1, 2, 3, 4, 6, 7, 8],
-    [0, 1, 2, 3, 4, 5, 7, 8],
-    [0, 1, 2, 3, 4, 5, 6, 8],
-    [0, 1, 2, 3, 4, 5, 6, 7],
-    [10, 11, 12, 13, 14, 15, 16, 17],
-    [9, 11, 12, 13, 14, 15, 16, 17],
-    [9, 10, 12, 13, 14, 15, 16, 17],
-    [9, 10, 11, 13, 14, 15, 16, 17],
-    [9, 10, 11, 12, 14, 15, 16, 17],
-    [9, 10, 11, 12, 13, 15, 16, 17],
-    [9, 10, 11, 12, 13, 14, 16, 17],
-    [9, 10, 11, 12, 13, 14, 15, 17],
-    [9, 10, 11, 12, 13, 14, 15, 16],
-    [19, 20, 21, 22, 23, 24, 25, 26],
-    [18, 20, 21, 22, 23, 24, 25, 26],
-    [18, 19, 21, 22, 23, 24, 25, 26],
-    [18, 19, 20, 22, 23, 24, 25, 26],
-    [18, 19, 20, 21, 23, 24, 25, 26],
-    [18, 19, 20, 21, 22, 24, 25, 26],
-    [18, 19, 20, 21, 22, 23, 25, 26],
-    [18, 19, 20, 21, 22, 23, 24, 26],
-    [18, 19, 20, 21, 22, 23, 24, 25],
-    [28, 29, 30, 31, 32, 33, 34, 35],
-    [27, 29, 30, 31, 32, 33, 34, 35],
-    [27, 28, 30, 31, 32, 33, 34, 35],
-    [27, 28, 29, 31, 32, 33, 34, 35],
-    [27, 28, 29, 30, 32, 33, 34, 35],
-    [27, 28, 29, 30, 31, 33, 34, 35],
-    [27, 28, 29, 30, 31, 32, 34, 35],
-    [27, 28, 29, 30, 31, 32, 33, 35],
-    [27, 28, 29, 30, 31, 32, 33, 34],
-    [37, 38, 39, 40, 41, 42, 43, 44],
-    [36, 38, 39, 40, 41, 42, 43, 44],
-    [36, 37, 39, 40, 41, 42, 43, 44],
-    [36, 37, 38, 40, 41, 42, 43, 44],
-    [36, 37, 38, 39, 41, 42, 43, 44],
-    [36, 37, 38, 39, 40, 42, 43, 44],
-    [36, 37, 38, 39, 40, 41, 43, 44],
-    [36, 37, 38, 39, 40, 41, 42, 44],
-    [36, 37, 38, 39, 40, 41, 42, 43],
-    [46, 47, 48, 49, 50, 51, 52, 53],
-    [45, 47, 48, 49, 50, 51, 52, 53],
-    [45, 46, 48, 49, 50, 51, 52, 53],
-    [45, 46, 47, 49, 50, 51, 52, 53],
-    [45, 46, 47, 48, 50, 51, 52, 53],
-    [45, 46, 47, 48, 49, 51, 52, 53],
-    [45, 46, 47, 48, 49, 50, 52, 53],
-    [45, 46, 47, 48, 49, 50, 51, 53],
-    [45, 46, 47, 48, 49, 50, 51, 52],
-    [55, 56, 57, 58, 59, 60, 61, 62],
-    [54, 56, 57, 58, 59, 60, 61, 62],
-    [54, 55, 57, 58, 59, 60, 61, 62],
-    [54, 55, 56, 58, 59, 60, 61, 62],
-    [54, 55, 56, 57, 59, 60, 61, 62],
-    [54, 55, 56, 57, 58, 60, 61, 62],
-    [54, 55, 56, 57, 58, 59, 61, 62],
-    [54, 55, 56, 57, 58, 59, 60, 62],
-    [54, 55, 56, 57, 58, 59, 60, 61],
-    [64, 65, 66, 67, 68, 69, 70, 71],
-    [63, 65, 66, 67, 68, 69, 70, 71],
-    [63, 64, 66, 67, 68, 69, 70, 71],
-    [63, 64, 65, 67, 68, 69, 70, 71],
-    [63, 64, 65, 66, 68, 69, 70, 71],
-    [63, 64, 65, 66, 67, 69, 70, 71],
-    [63, 64, 65, 66, 67, 68, 70, 71],
-    [63, 64, 65, 66, 67, 68, 69, 71],
-    [63, 64, 65, 66, 67, 68, 69, 70],
-    [73, 74, 75, 76, 77, 78, 79, 80],
-    [72, 74, 75, 76, 77, 78, 79, 80],
-    [72, 73, 75, 76, 77, 78, 79, 80],
-    [72, 73, 74, 76, 77, 78, 79, 80],
-    [72, 73, 74, 75, 77, 78, 79, 80],
-    [72, 73, 74, 75, 76, 78, 79, 80],
-    [72, 73, 74, 75, 76, 77, 79, 80],
-    [72, 73, 74, 75, 76, 77, 78, 80],
-    [72, 73, 74, 75, 76, 77, 78, 79],
-];
-
-pub const SAME_COL: [[usize; 8]; SIZE] = [
-    [9, 18, 27, 36, 45, 54, 63, 72],
-    [10, 19, 28, 37, 46, 55, 64, 73],
-    [11, 20, 29, 38, 47, 56, 65, 74],
-    [12, 21, 30, 39, 48, 57, 66, 75],
-    [13, 22, 31, 40, 49, 58, 67, 76],
-    [14, 23, 32, 41, 50, 59, 68, 77],
-    [15, 24, 33, 42, 51, 60, 69, 78],
-    [16, 25, 34, 43, 52, 61, 70, 79],
-    [17, 26, 35, 44, 53, 62, 71, 80],
-    [0, 18, 27, 36, 45, 54, 63, 72],
-    [1, 19, 28, 37, 46, 55, 64, 73],
-    [2, 20, 29, 38, 47, 56, 65, 74],
-    [3, 21, 30, 39, 48, 57, 66, 75],
-    [4, 22, 31, 40, 49, 58, 67, 76],
-    [5, 23, 32, 41, 50, 59, 68, 77],
-    [6, 24, 33, 42, 51, 60, 69, 78],
-    [7, 25, 34, 43, 52, 61, 70, 79],
-    [8, 26, 35, 44, 53, 62, 71, 80],
-    [0, 9, 27, 36, 45, 54, 63, 72],
-    [1, 10, 28, 37, 46, 55, 64, 73],
-    [2, 11, 29, 38, 47, 56, 65, 74],
-    [3, 12, 30, 39, 48, 57, 66, 75],
-    [4, 13, 31, 40, 49, 58, 67, 76],
-    [5, 14, 32, 41, 50, 59, 68, 77],
-    [6, 15, 33, 42, 51, 60, 69, 78],
-    [7, 16, 34, 43, 52, 61, 70, 79],
-    [8, 17, 35, 44, 53, 62, 71, 80],
-    [0, 9, 18, 36, 45, 54, 63, 72],
-    [1, 10, 19, 37, 46, 55, 64, 73],
-    [2, 11, 20, 38, 47, 56, 65, 74],
-    [3, 12, 21, 39, 48, 57, 66, 75],
-    [4, 13, 22, 40, 49, 58, 67, 76],
-    [5, 14, 23, 41, 50, 59, 68, 77],
-    [6, 15, 24, 42, 51, 60, 69, 78],
-    [7, 16, 25, 43, 52, 61, 70, 79],
-    [8, 17, 26, 44, 53, 62, 71, 80],
-    [0, 9, 18, 27, 45, 54, 63, 72],
-    [1, 10, 19, 28, 46, 55, 64, 73],
-    [2, 11, 20, 29, 47, 56, 65, 74],
-    [3, 12, 21, 30, 48, 57, 66, 75],
-    [4, 13, 22, 31, 49, 58, 67, 76],
-    [5, 14, 23, 32, 50, 59, 68, 77],
-    [6, 15, 24, 33, 51, 60, 69, 78],
-    [7, 16, 25, 34, 52, 61, 70, 79],
-    [8, 17, 26, 35, 53, 62, 71, 80],
-    [0, 9, 18, 27, 36, 54, 63, 72],
-    [1, 10, 19, 28, 37, 55, 64, 73],
-    [2, 11, 20, 29, 38, 56, 65, 74],
-    [3, 12, 21, 30, 39, 57, 66, 75],
-    [4, 13, 22, 31, 40, 58, 67, 76],
-    [5, 14, 23, 32, 41, 59, 68, 77],
-    [6, 15, 24, 33, 42, 60, 69, 78],
-    [7, 16, 25, 34, 43, 61, 70, 79],
-    [8, 17, 26, 35, 44, 62, 71, 80],
-    [0, 9, 18, 27, 36, 45, 63, 72],
-    [1, 10, 19, 28, 37, 46, 64, 73],
-    [2, 11, 20, 29, 38, 47, 65, 74],
-    [3, 12, 21, 30, 39, 48, 66, 75],
-    [4, 13, 22, 31, 40, 49, 67, 76],
-    [5, 14, 23, 32, 41, 50, 68, 77],
-    [6, 15, 24, 33, 42, 51, 69, 78],
-    [7, 16, 25, 34, 43, 52, 70, 79],
-    [8, 17, 26, 35, 44, 53, 71, 80],
-    [0, 9, 18, 27, 36, 45, 54, 72],
-    [1, 10, 19, 28, 37, 46, 55, 73],
-    [2, 11, 20, 29, 38, 47, 56, 74],
-    [3, 12, 21, 30, 39, 48, 57, 75],
-    [4, 13, 22, 31, 40, 49, 58, 76],
-    [5, 14, 23, 32, 41, 50, 59, 77],
-    [6, 15, 24, 33, 42, 51, 60, 78],
-    [7, 16, 25, 34, 43, 52, 61, 79],
-    [8, 17, 26, 35, 44, 53, 62, 80],
-    [0, 9, 18, 27, 36, 45, 54, 63],
-    [1, 10, 19, 28, 37, 46, 55, 64],
-    [2, 11, 20, 29, 38, 47, 56, 65],
-    [3, 12, 21, 30, 39, 48, 57, 66],
-    [4, 13, 22, 31, 40, 49, 58, 67],
-    [5, 14, 23, 32, 41, 50, 59, 68],
-    [6, 15, 24, 33, 42, 51, 60, 69],
-    [7, 16, 25, 34, 43, 52, 61, 70],
-    [8, 17, 26, 35, 44, 53, 62, 71],
-];
-
 #[allow(unused)]
 pub const SAME_CELL: [[usize; 8]; SIZE] = [
     [1, 2, 9, 10, 11, 18, 19, 20],
@@ -539,6 +371,7 @@ pub const COLS: [[usize; 9]; 9] = [
     [8, 17, 26, 35, 44, 53, 62, 71, 80],
 ];
 
+#[cfg_attr(not(feature = "std"), allow(unused))]
 pub const CELL_LOOKUP: [usize; SIZE] = [
     0, 0, 0, 1, 1, 1, 2, 2, 2, 0, 0, 0, 1, 1, 1, 2, 2, 2, 0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4,
     4, 5, 5, 5, 3, 3, 3, 4, 4, 4, 5, 5, 5, 3, 3, 3, 4, 4, 4, 5, 5, 5, 6, 6, 6, 7, 7, 7, 8, 8, 8, 6,
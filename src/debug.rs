@@ -1,4 +1,8 @@
-use crate::{consts, sudoku::Sudoku};
+use crate::{
+    consts,
+    shape::Shape,
+    sudoku::{digit_char, Sudoku},
+};
 
 use std::fmt::Write;
 
@@ -71,6 +75,67 @@ pub fn pretty_print_alternatives(sudoku: &Sudoku) -> Option<String> {
     Some(ret)
 }
 
+/// Like [`pretty_print`], for a [`Sudoku`] of any size or region shape. Walls
+/// are drawn between cells in different regions, and only the lines that have
+/// a wall somewhere take up space.
+#[allow(unused)]
+#[must_use]
+pub fn pretty_print_grid<S: Shape>(sudoku: &Sudoku<S>) -> Option<String> {
+    let shape = sudoku.shape();
+    let width = shape.width();
+    let region = |row: usize, col: usize| shape.units_of(width * row + col)[2];
+    let wall_left = |row, col| col == 0 || col == width || region(row, col - 1) != region(row, col);
+    let wall_above =
+        |row, col| row == 0 || row == width || region(row - 1, col) != region(row, col);
+    let cols: Vec<bool> = (0..=width)
+        .map(|col| (0..width).any(|row| wall_left(row, col)))
+        .collect();
+    let rows: Vec<bool> = (0..=width)
+        .map(|row| (0..width).any(|col| wall_above(row, col)))
+        .collect();
+
+    let mut ret = String::new();
+    for (row, &has_line) in rows.iter().enumerate() {
+        if has_line {
+            for (col, &has_wall) in cols.iter().enumerate() {
+                if has_wall {
+                    let vertical = (row > 0 && wall_left(row - 1, col))
+                        || (row < width && wall_left(row, col));
+                    let horizontal = (col > 0 && wall_above(row, col - 1))
+                        || (col < width && wall_above(row, col));
+                    let c = match (vertical, horizontal) {
+                        (true, true) => '+',
+                        (true, false) => '|',
+                        (false, true) => '-',
+                        (false, false) => ' ',
+                    };
+                    write!(ret, "{c}").ok()?;
+                }
+                if col < width {
+                    write!(ret, "{}", if wall_above(row, col) { '-' } else { ' ' }).ok()?;
+                }
+            }
+            if row < width {
+                writeln!(ret).ok()?;
+            }
+        }
+        if row < width {
+            for (col, &has_wall) in cols.iter().enumerate() {
+                if has_wall {
+                    write!(ret, "{}", if wall_left(row, col) { '|' } else { ' ' }).ok()?;
+                }
+                if col < width {
+                    let digit = sudoku.digits[width * row + col];
+                    let c = if digit == 0 { ' ' } else { digit_char(digit) };
+                    write!(ret, "{c}").ok()?;
+                }
+            }
+            writeln!(ret).ok()?;
+        }
+    }
+    Some(ret)
+}
+
 #[cfg(test)]
 #[allow(clippy::panic_in_result_fn)]
 mod tests {
@@ -101,6 +166,52 @@ mod tests {
         Ok(())
     }
 
+    #[rstest]
+    #[case("123456789456789123789123456231674895875912364694538217317265948542897631968341572")]
+    #[case("1................................................................................")]
+    fn test_pretty_print_grid_matches(#[case] input: &str) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let grid = Sudoku::parse(&geometry, input)?;
+        let sudoku = Sudoku::from_str(input)?;
+
+        assert_eq!(pretty_print_grid(&grid), pretty_print(&sudoku));
+        assert_eq!(pretty_print_grid(&sudoku), pretty_print(&sudoku));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        Geometry::rectangular(2, 3),
+        "1.4.5.........65.1....4...521.2.....",
+        "+---+---+\n|1 4| 5 |\n|   |   |\n+---+---+\n|  6|5 1|\n|   | 4 |\n+---+---+\n|  5|21 |\n|2  |   |\n+---+---+",
+    )]
+    #[case(
+        Geometry::rectangular(3, 2),
+        "1.4.5.........65.1....4...521.2.....",
+        "+--+--+--+\n|1 |4 |5 |\n|  |  |  |\n|  |65| 1|\n+--+--+--+\n|  |  |4 |\n|  |52|1 |\n|2 |  |  |\n+--+--+--+",
+    )]
+    #[case(
+        Geometry::square(4),
+        "1..............................................................................................................................................................................................................................................................G",
+        "+----+----+----+----+\n|1   |    |    |    |\n|    |    |    |    |\n|    |    |    |    |\n|    |    |    |    |\n+----+----+----+----+\n|    |    |    |    |\n|    |    |    |    |\n|    |    |    |    |\n|    |    |    |    |\n+----+----+----+----+\n|    |    |    |    |\n|    |    |    |    |\n|    |    |    |    |\n|    |    |    |    |\n+----+----+----+----+\n|    |    |    |    |\n|    |    |    |    |\n|    |    |    |    |\n|    |    |    |   G|\n+----+----+----+----+",
+    )]
+    #[case(
+        Geometry::jigsaw(&[0, 0, 0, 1, 2, 0, 1, 1, 2, 2, 3, 1, 2, 3, 3, 3]),
+        "1..2.3....4.2...",
+        "+-----+-+\n|1    |2|\n+-+ +-+ |\n| |3|   |\n| +-+-+ |\n|   |4| |\n| +-+ +-+\n|2|     |\n+-+-----+",
+    )]
+    fn test_pretty_print_grid(
+        #[case] geometry: Result<Geometry>,
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let geometry = geometry?;
+        let grid = Sudoku::parse(&geometry, input)?;
+
+        assert_eq!(pretty_print_grid(&grid).ok_or(Error::ParseError)?, expected);
+        Ok(())
+    }
+
     #[rstest]
     #[case(
         ".................................................................................",
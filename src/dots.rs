@@ -0,0 +1,412 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    board::Board,
+    constraint::{options, parse_cell, restrict, supported, Constraint},
+    geometry::Geometry,
+    technique::Progress,
+    Error, Result,
+};
+
+/// A mark on the edge between two orthogonally adjacent cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dot {
+    /// A white Kropki dot: the digits are consecutive.
+    White,
+    /// A black Kropki dot: one digit is twice the other.
+    Black,
+    /// The digits add up to 10.
+    X,
+    /// The digits add up to 5.
+    V,
+    /// The digit in the first cell is greater than in the second.
+    GreaterThan,
+}
+
+impl fmt::Display for Dot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::White => write!(f, "white"),
+            Self::Black => write!(f, "black"),
+            Self::X => write!(f, "x"),
+            Self::V => write!(f, "v"),
+            Self::GreaterThan => write!(f, ">"),
+        }
+    }
+}
+
+impl FromStr for Dot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "white" => Ok(Self::White),
+            "black" => Ok(Self::Black),
+            "x" => Ok(Self::X),
+            "v" => Ok(Self::V),
+            ">" | "greater" => Ok(Self::GreaterThan),
+            _ => Err(Error::ParseError),
+        }
+    }
+}
+
+impl Dot {
+    /// Returns true if the digits on both sides of the dot satisfy it.
+    #[must_use]
+    pub const fn holds(self, first: u8, second: u8) -> bool {
+        match self {
+            Self::White => first.abs_diff(second) == 1,
+            Self::Black => first == 2 * second || second == 2 * first,
+            Self::X => first + second == 10,
+            Self::V => first + second == 5,
+            Self::GreaterThan => first > second,
+        }
+    }
+
+    /// The dots that a negative constraint on this dot looks for: a pair
+    /// without any of them can't satisfy this dot.
+    const fn family(self) -> Option<&'static [Self]> {
+        match self {
+            Self::White | Self::Black => Some(&[Self::White, Self::Black]),
+            Self::X | Self::V => Some(&[Self::X, Self::V]),
+            Self::GreaterThan => None,
+        }
+    }
+}
+
+/// A dot between two orthogonally adjacent cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub first: usize,
+    pub second: usize,
+    pub dot: Dot,
+}
+
+/// The dots of a puzzle, and the negative constraints that forbid a
+/// relation between every pair of neighbors without a dot.
+///
+/// A negative white constraint only applies to pairs without a white or a
+/// black dot, as in Kropki puzzles. The non-consecutive rule applies to every
+/// pair without a white dot, so a black dot can't join 1 and 2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dots {
+    /// Every pair with the relation it must satisfy, or must not when the
+    /// flag is false.
+    relations: Vec<(usize, usize, Dot, bool)>,
+}
+
+impl Dots {
+    /// The given dots, with a negative constraint for every dot in
+    /// `negative`, and the non-consecutive rule if `nonconsecutive` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cells of an edge are not orthogonally
+    /// adjacent, or if a greater-than sign is negative.
+    pub fn new(
+        geometry: &Geometry,
+        edges: &[Edge],
+        negative: &[Dot],
+        nonconsecutive: bool,
+    ) -> Result<Self> {
+        let width = geometry.width();
+        let adjacent = |first: usize, second: usize| {
+            let (low, high) = (first.min(second), first.max(second));
+            high < geometry.size()
+                && (high - low == width || (high - low == 1 && high % width != 0))
+        };
+        if edges.iter().any(|edge| !adjacent(edge.first, edge.second)) {
+            return Err(Error::IndexError);
+        }
+        let mut relations: Vec<_> = edges
+            .iter()
+            .map(|edge| (edge.first, edge.second, edge.dot, true))
+            .collect();
+
+        let pairs = || {
+            (0..geometry.size()).flat_map(move |idx| {
+                let right = (idx % width + 1 < width).then_some((idx, idx + 1));
+                let below = (idx + width < geometry.size()).then_some((idx, idx + width));
+                right.into_iter().chain(below)
+            })
+        };
+        let marked = |first: usize, second: usize, family: &[Dot]| {
+            edges.iter().any(|edge| {
+                family.contains(&edge.dot)
+                    && [(edge.first, edge.second), (edge.second, edge.first)]
+                        .contains(&(first, second))
+            })
+        };
+
+        for &dot in negative {
+            let family = dot.family().ok_or(Error::ParseError)?;
+            for (first, second) in pairs() {
+                if !marked(first, second, family) {
+                    relations.push((first, second, dot, false));
+                }
+            }
+        }
+        if nonconsecutive {
+            for (first, second) in pairs() {
+                if !marked(first, second, &[Dot::White]) {
+                    relations.push((first, second, Dot::White, false));
+                }
+            }
+        }
+        Ok(Self { relations })
+    }
+
+    /// Parses a puzzle's dots, one edge per row of text such as
+    /// `white: r1c1 r1c2`.
+    ///
+    /// The dots are `white`, `black`, `x`, `v` and `>`, where the first cell
+    /// of `>` holds the greater digit. Negative constraints are listed as
+    /// `negative: white black`, and a line `nonconsecutive` adds the
+    /// non-consecutive rule. Empty lines and lines starting with `#` are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line is malformed, or if the dots don't fit the
+    /// board.
+    pub fn parse(geometry: &Geometry, s: &str) -> Result<Self> {
+        let mut edges = Vec::new();
+        let mut negative = Vec::new();
+        let mut nonconsecutive = false;
+        for line in s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            if line.eq_ignore_ascii_case("nonconsecutive") {
+                nonconsecutive = true;
+                continue;
+            }
+            let (kind, rest) = line.split_once(':').ok_or(Error::ParseError)?;
+            if kind.trim().eq_ignore_ascii_case("negative") {
+                for dot in rest.split_whitespace() {
+                    negative.push(Dot::from_str(dot)?);
+                }
+                continue;
+            }
+            let cells: Vec<usize> = rest
+                .split_whitespace()
+                .map(|cell| parse_cell(geometry, cell))
+                .collect::<Result<_>>()?;
+            let [first, second] = cells[..] else {
+                return Err(Error::ParseError);
+            };
+            edges.push(Edge {
+                first,
+                second,
+                dot: Dot::from_str(kind.trim())?,
+            });
+        }
+        Self::new(geometry, &edges, &negative, nonconsecutive)
+    }
+}
+
+impl<B: Board> Constraint<B> for Dots {
+    fn propagate(&self, board: &mut B) -> Result<Progress> {
+        let mut changed = false;
+        for &(first, second, dot, holds) in &self.relations {
+            // adjacent cells share a row or a column, so they never repeat
+            let related = |a, b| a != b && dot.holds(a, b) == holds;
+            let (first_mask, second_mask) = (options(board, first), options(board, second));
+            let second_allowed = supported(first_mask, second_mask, related);
+            let first_allowed = supported(second_mask, first_mask, |b, a| related(a, b));
+            changed |= restrict(board, second, second_allowed)? == Progress::Changed;
+            changed |= restrict(board, first, first_allowed)? == Progress::Changed;
+        }
+        Ok(changed.into())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::{constraint::digits, solver, Pipeline, Sudoku};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Dot::White, 4, 5, true)]
+    #[case(Dot::White, 5, 4, true)]
+    #[case(Dot::White, 4, 6, false)]
+    #[case(Dot::Black, 3, 6, true)]
+    #[case(Dot::Black, 8, 4, true)]
+    #[case(Dot::Black, 3, 5, false)]
+    #[case(Dot::X, 3, 7, true)]
+    #[case(Dot::X, 5, 5, true)]
+    #[case(Dot::V, 1, 4, true)]
+    #[case(Dot::V, 2, 4, false)]
+    #[case(Dot::GreaterThan, 6, 2, true)]
+    #[case(Dot::GreaterThan, 2, 6, false)]
+    fn test_holds(
+        #[case] dot: Dot,
+        #[case] first: u8,
+        #[case] second: u8,
+        #[case] holds: bool,
+    ) -> Result<()> {
+        assert_eq!(dot.holds(first, second), holds);
+        assert_eq!(Dot::from_str(&dot.to_string())?, dot);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("black: r1c1 r1c2", vec![1, 2, 3, 4, 6, 8])]
+    #[case("x: r1c1 r1c2", vec![1, 2, 3, 4, 6, 7, 8, 9])]
+    #[case("v: r1c1 r2c1", vec![1, 2, 3, 4])]
+    #[case(">: r1c1 r1c2", vec![2, 3, 4, 5, 6, 7, 8, 9])]
+    #[case(">: r1c2 r1c1", vec![1, 2, 3, 4, 5, 6, 7, 8])]
+    #[case("white: r1c1 r1c2", vec![1, 2, 3, 4, 5, 6, 7, 8, 9])]
+    fn test_propagate(#[case] line: &str, #[case] expected: Vec<u8>) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        let dots = Dots::parse(&geometry, line)?;
+
+        dots.propagate(&mut sudoku)?;
+        assert_eq!(digits(options(&sudoku, 0)).collect::<Vec<_>>(), expected);
+        assert_eq!(dots.propagate(&mut sudoku)?, Progress::Unchanged);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nonconsecutive() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        sudoku.place(40, 5);
+        let dots = Dots::parse(&geometry, "white: r5c5 r5c6\nnonconsecutive")?;
+
+        dots.propagate(&mut sudoku)?;
+        assert_eq!(digits(options(&sudoku, 41)).collect::<Vec<_>>(), [4, 6]);
+        for idx in [31, 39, 49] {
+            assert_eq!(
+                digits(options(&sudoku, idx)).collect::<Vec<_>>(),
+                [1, 2, 3, 7, 8, 9]
+            );
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("negative: white", vec![1, 4])]
+    #[case("nonconsecutive", vec![4])]
+    fn test_black_dot_with_negative_white(
+        #[case] rule: &str,
+        #[case] expected: Vec<u8>,
+    ) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        sudoku.place(0, 2);
+        let dots = Dots::parse(&geometry, &format!("black: r1c1 r1c2\n{rule}"))?;
+
+        dots.propagate(&mut sudoku)?;
+        assert_eq!(digits(options(&sudoku, 1)).collect::<Vec<_>>(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_negative_xv() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        sudoku.place(0, 2);
+        Dots::parse(&geometry, "negative: x v")?.propagate(&mut sudoku)?;
+        assert_eq!(
+            digits(options(&sudoku, 1)).collect::<Vec<_>>(),
+            [1, 4, 5, 6, 7, 9]
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("white: r1c1 r1c2", &[(0, 3), (1, 5)])]
+    #[case("black: r1c1 r2c1", &[(0, 3), (9, 5)])]
+    #[case(">: r1c1 r1c2", &[(0, 3), (1, 5)])]
+    #[case("negative: white", &[(0, 3), (1, 4)])]
+    #[case("black: r1c1 r1c2\nnonconsecutive", &[(0, 1), (1, 2)])]
+    fn test_violated(#[case] s: &str, #[case] givens: &[(usize, usize)]) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let dots = Dots::parse(&geometry, s)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        for &(idx, digit) in givens {
+            sudoku.place(idx, digit);
+        }
+
+        assert!(dots.propagate(&mut sudoku).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let dots = Dots::parse(
+            &geometry,
+            "
+            x: r1c1 r2c1
+            x: r1c3 r1c4
+            x: r1c3 r2c3
+            v: r2c1 r3c1
+            x: r2c2 r3c2
+            v: r2c6 r3c6
+            x: r2c8 r3c8
+            x: r2c9 r3c9
+            x: r3c1 r4c1
+            v: r3c2 r4c2
+            v: r4c2 r4c3
+            x: r4c3 r5c3
+            v: r4c6 r4c7
+            x: r5c2 r6c2
+            x: r5c3 r5c4
+            v: r5c4 r6c4
+            x: r5c6 r5c7
+            x: r5c8 r5c9
+            x: r6c1 r7c1
+            v: r6c2 r6c3
+            v: r6c2 r7c2
+            x: r6c3 r7c3
+            x: r7c2 r7c3
+            v: r7c4 r8c4
+            x: r7c8 r8c8
+            x: r8c1 r9c1
+            x: r8c3 r9c3
+            v: r8c5 r9c5
+            negative: x v
+            ",
+        )?;
+        let puzzle = Sudoku::parse(
+            &geometry,
+            "................................1........................................7.......",
+        )?;
+        let mut pipeline = Pipeline::default();
+        pipeline.extend([Box::new(dots) as Box<dyn Constraint<Sudoku<Geometry>>>]);
+
+        // with `all_solutions` this also proves that the solution is unique
+        assert_eq!(
+            solver::solve_with(puzzle.clone(), &pipeline)?.0.to_string(),
+            "693784512487512936125963874932651487568247391741398625319475268856129743274836159"
+        );
+        if cfg!(feature = "all_solutions") {
+            assert!(matches!(
+                solver::solve_with(puzzle, &Pipeline::default()),
+                Err(Error::MultipleSolutions)
+            ));
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("white r1c1 r1c2")]
+    #[case("white: r1c1")]
+    #[case("white: r1c1 r1c2 r1c3")]
+    #[case("white: r1c1 r2c2")]
+    #[case("white: r1c9 r2c1")]
+    #[case("o: r1c1 r1c2")]
+    #[case("negative: >")]
+    #[case("negative: o")]
+    fn test_parse_errors(#[case] s: &str) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        assert!(Dots::parse(&geometry, s).is_err());
+        Ok(())
+    }
+}
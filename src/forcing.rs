@@ -0,0 +1,284 @@
+use std::fmt;
+
+use crate::{
+    candidate::{self, Candidate, CellName},
+    consts,
+    solver::{check_constraints, place_and_propagate},
+    sudoku::Sudoku,
+    Result,
+};
+
+/// The number of rounds of `check_constraints` run on each branch before its
+/// consequences are compared.
+const MAX_ROUNDS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForcingKind {
+    /// Assuming a candidate is true leads to a contradiction.
+    Nishio,
+    /// Every candidate of a cell leads to the same conclusion.
+    Cell,
+    /// Every position of a digit in a unit leads to the same conclusion.
+    Unit,
+    /// A candidate being true and false leads to the same conclusion.
+    Digit,
+}
+
+impl fmt::Display for ForcingKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Nishio => write!(f, "Nishio"),
+            Self::Cell => write!(f, "Cell Forcing Chain"),
+            Self::Unit => write!(f, "Unit Forcing Chain"),
+            Self::Digit => write!(f, "Digit Forcing Chain"),
+        }
+    }
+}
+
+/// The premise of a single branch: a candidate assumed to be true or false.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Assumption {
+    pub candidate: Candidate,
+    pub holds: bool,
+}
+
+impl fmt::Display for Assumption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let relation = if self.holds { "=" } else { "<>" };
+        let Candidate { idx, digit } = self.candidate;
+        write!(f, "{}{relation}{digit}", CellName(idx))
+    }
+}
+
+/// A set of branches that cover every possibility, together with the
+/// candidates that are false in every branch that does not contradict itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForcingChain {
+    pub kind: ForcingKind,
+    pub branches: Vec<Assumption>,
+    pub eliminations: Vec<Candidate>,
+}
+
+impl fmt::Display for ForcingChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.kind)?;
+        for (i, branch) in self.branches.iter().enumerate() {
+            let separator = if i == 0 { " " } else { " | " };
+            write!(f, "{separator}{branch}")?;
+        }
+        write!(f, " =>")?;
+        self.eliminations
+            .iter()
+            .try_for_each(|candidate| write!(f, " {candidate}"))
+    }
+}
+
+/// Find all forcing chains of every kind that eliminate at least one
+/// candidate.
+#[must_use]
+pub fn find_forcing_chains(sudoku: &Sudoku) -> Vec<ForcingChain> {
+    let mut chains = find_nishio(sudoku);
+    chains.extend(find_cell_forcing_chains(sudoku));
+    chains.extend(find_unit_forcing_chains(sudoku));
+    chains.extend(find_digit_forcing_chains(sudoku));
+    chains
+}
+
+/// Apply the eliminations of the first forcing chain found, if any. Nishio is
+/// tried first, followed by cell, unit and digit forcing chains.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn apply_forcing_chain(sudoku: &mut Sudoku) -> Result<Option<ForcingChain>> {
+    let Some(chain) = find_nishio(sudoku)
+        .into_iter()
+        .next()
+        .or_else(|| find_cell_forcing_chains(sudoku).into_iter().next())
+        .or_else(|| find_unit_forcing_chains(sudoku).into_iter().next())
+        .or_else(|| find_digit_forcing_chains(sudoku).into_iter().next())
+    else {
+        return Ok(None);
+    };
+    candidate::eliminate(sudoku, &chain.eliminations)?;
+    Ok(Some(chain))
+}
+
+/// Find candidates that lead to a contradiction when assumed to be true.
+#[must_use]
+pub fn find_nishio(sudoku: &Sudoku) -> Vec<ForcingChain> {
+    candidate::candidates(sudoku)
+        .map(|candidate| Assumption {
+            candidate,
+            holds: true,
+        })
+        .filter(|&assumption| follow(sudoku, assumption).is_none())
+        .map(|assumption| ForcingChain {
+            kind: ForcingKind::Nishio,
+            branches: vec![assumption],
+            eliminations: vec![assumption.candidate],
+        })
+        .collect()
+}
+
+/// Find conclusions shared by every candidate of a single cell.
+#[must_use]
+pub fn find_cell_forcing_chains(sudoku: &Sudoku) -> Vec<ForcingChain> {
+    (0..consts::SIZE)
+        .filter(|&idx| sudoku.digits[idx] == 0)
+        .filter_map(|idx| {
+            let branches = candidate::digits(sudoku.bitboard[idx])
+                .map(|digit| Assumption {
+                    candidate: Candidate::new(idx, digit),
+                    holds: true,
+                })
+                .collect();
+            forcing_chain(sudoku, ForcingKind::Cell, branches)
+        })
+        .collect()
+}
+
+/// Find conclusions shared by every position of a digit within a unit.
+#[must_use]
+pub fn find_unit_forcing_chains(sudoku: &Sudoku) -> Vec<ForcingChain> {
+    candidate::units()
+        .flat_map(|unit| {
+            (1..=consts::WIDTH).filter_map(move |digit| {
+                let branches = candidate::cells_with_digit(sudoku, unit, digit)
+                    .map(|idx| Assumption {
+                        candidate: Candidate::new(idx, digit),
+                        holds: true,
+                    })
+                    .collect();
+                forcing_chain(sudoku, ForcingKind::Unit, branches)
+            })
+        })
+        .collect()
+}
+
+/// Find conclusions that follow both from a candidate and from its negation.
+#[must_use]
+pub fn find_digit_forcing_chains(sudoku: &Sudoku) -> Vec<ForcingChain> {
+    candidate::candidates(sudoku)
+        .filter_map(|candidate| {
+            let branches = [true, false]
+                .map(|holds| Assumption { candidate, holds })
+                .to_vec();
+            forcing_chain(sudoku, ForcingKind::Digit, branches)
+        })
+        .collect()
+}
+
+/// Follows every branch, and collects the candidates that are false in all
+/// branches that do not lead to a contradiction. Returns `None` if there are
+/// fewer than two branches, if every branch is a contradiction, or if there is
+/// nothing to eliminate.
+fn forcing_chain(
+    sudoku: &Sudoku,
+    kind: ForcingKind,
+    branches: Vec<Assumption>,
+) -> Option<ForcingChain> {
+    if branches.len() < 2 {
+        return None;
+    }
+    let outcomes: Vec<Sudoku> = branches
+        .iter()
+        .filter_map(|&assumption| follow(sudoku, assumption))
+        .collect();
+    if outcomes.is_empty() {
+        return None;
+    }
+    let eliminations: Vec<Candidate> = candidate::candidates(sudoku)
+        .filter(|&candidate| outcomes.iter().all(|outcome| is_false(outcome, candidate)))
+        .collect();
+    if eliminations.is_empty() {
+        None
+    } else {
+        Some(ForcingChain {
+            kind,
+            branches,
+            eliminations,
+        })
+    }
+}
+
+/// Applies the assumption to a copy of the Sudoku and runs a bounded number of
+/// constraint checks on it. Returns `None` if this leads to a contradiction.
+fn follow(sudoku: &Sudoku, assumption: Assumption) -> Option<Sudoku> {
+    let mut branch = sudoku.clone();
+    let Candidate { idx, digit } = assumption.candidate;
+    if assumption.holds {
+        place_and_propagate(&mut branch, idx, digit).ok()?;
+    } else {
+        candidate::eliminate(&mut branch, &[assumption.candidate]).ok()?;
+    }
+    for _ in 0..MAX_ROUNDS {
+        if branch.is_solved() {
+            break;
+        }
+        let bitboard = branch.bitboard;
+        check_constraints(&mut branch).ok()?;
+        if branch.bitboard == bitboard {
+            break;
+        }
+    }
+    Some(branch)
+}
+
+const fn is_false(outcome: &Sudoku, candidate: Candidate) -> bool {
+    outcome.digits[candidate.idx] != candidate.digit
+        && outcome.bitboard[candidate.idx] & (1 << candidate.digit) == 0
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::{assert_eliminations_are_sound, with_candidates};
+    use rstest::rstest;
+
+    #[test]
+    fn test_nishio() {
+        // r1c1 = 1 leaves 3 as the only candidate in both r1c2 and r1c3
+        let sudoku = with_candidates(&[(0, &[1, 2]), (1, &[1, 3]), (2, &[1, 3])]);
+        let chains = find_nishio(&sudoku);
+
+        assert!(chains
+            .iter()
+            .any(|chain| chain.to_string() == "Nishio: r1c1=1 => r1c1<>1"));
+    }
+
+    #[test]
+    fn test_cell_forcing_chain() {
+        // r1c1 = 1 places 3 in r1c5 and 2 in r5c5, so either way r5c1 is not 2
+        let sudoku = with_candidates(&[(0, &[1, 2]), (4, &[1, 3]), (40, &[2, 3])]);
+        let chains = find_cell_forcing_chains(&sudoku);
+        let chain = chains
+            .iter()
+            .find(|chain| chain.branches[0].candidate.idx == 0);
+
+        assert!(chain.is_some_and(|chain| chain.eliminations.contains(&Candidate::new(36, 2))));
+    }
+
+    #[rstest]
+    #[case(
+        "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..",
+        "397514628415628973286937154978362415631845792542791386729456831163289547854173269"
+    )]
+    #[case(
+        "...6..1..7....4...3.....2.7.9...3.2....47.9.3........1.38.6..5.2.......9..7..5...",
+        "589627134726134895341859267894513726152476983673982541938761452215348679467295318"
+    )]
+    fn test_eliminations_are_sound(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let sudoku = assert_eliminations_are_sound(
+            input,
+            expected,
+            find_forcing_chains,
+            apply_forcing_chain,
+            |chain| &chain.eliminations,
+        )?;
+        assert_eq!(sudoku.to_string(), expected);
+        Ok(())
+    }
+}
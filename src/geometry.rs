@@ -0,0 +1,630 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use crate::{
+    consts::BitWidth,
+    shape::{Shape, MAX_WIDTH},
+    Error, Result,
+};
+
+/// A [`Shape`] whose tables are built at runtime from the region of every
+/// cell, so that boxes can have any shape, and that can have extra units and
+/// chess moves.
+///
+/// Grids of rectangular boxes can also use the tables that
+/// [`Boxes`](crate::Boxes) builds at compile time. The tables of a geometry
+/// are shared, so cloning it is cheap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Geometry(Arc<Tables>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tables {
+    width: usize,
+    /// The region (box) of each cell, like `consts::CELL_LOOKUP`.
+    regions: Vec<usize>,
+    /// Every unit as a list of cells: rows, then columns, then regions.
+    units: Vec<Vec<usize>>,
+    /// The units that each cell belongs to, as indices into `units`.
+    units_of: Vec<Vec<usize>>,
+    /// The other cells of the row, column and region of each cell.
+    peers: Vec<[Vec<usize>; 3]>,
+    /// Chess moves whose cells see each other without sharing a unit.
+    moves: Vec<ChessMove>,
+    /// The cells that share a unit with each cell, or that are a chess move
+    /// away, in ascending order.
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Geometry {
+    /// A grid of square boxes, each holding `box_size` x `box_size` cells. A
+    /// box size of 3 is the classic Sudoku.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the box size is not between 2 and 5.
+    pub fn square(box_size: usize) -> Result<Self> {
+        Self::rectangular(box_size, box_size)
+    }
+
+    /// A grid of boxes that are `box_height` rows tall and `box_width`
+    /// columns wide, such as a 6x6 grid with 2x3 boxes. The grid is
+    /// `box_height * box_width` cells wide.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a side of the box is shorter than 2, or if the
+    /// grid would be wider than 25 cells.
+    pub fn rectangular(box_height: usize, box_width: usize) -> Result<Self> {
+        let width = box_height * box_width;
+        if box_height < 2 || box_width < 2 || width > MAX_WIDTH {
+            return Err(Error::IndexError);
+        }
+        let regions = (0..width * width)
+            .map(|idx| {
+                let (row, col) = (idx / width, idx % width);
+                row / box_height * box_height + col / box_width
+            })
+            .collect();
+        Ok(Self::from_regions(width, regions))
+    }
+
+    /// A jigsaw grid, where the boxes are replaced by the given regions. The
+    /// region of every cell is listed row by row, and numbered from 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grid is not square or wider than 25 cells, or
+    /// if a region is not connected or doesn't have as many cells as a row.
+    pub fn jigsaw(regions: &[usize]) -> Result<Self> {
+        let width = (1..=MAX_WIDTH)
+            .find(|&width| width * width == regions.len())
+            .ok_or(Error::IndexError)?;
+        let mut sizes = vec![0; width];
+        for &region in regions {
+            *sizes.get_mut(region).ok_or(Error::IndexError)? += 1;
+        }
+        if sizes.iter().any(|&size| size != width)
+            || (0..width).any(|region| !is_connected(width, regions, region))
+        {
+            return Err(Error::IndexError);
+        }
+        Ok(Self::from_regions(width, regions.to_vec()))
+    }
+
+    fn from_regions(width: usize, regions: Vec<usize>) -> Self {
+        let size = width * width;
+        let rows = (0..width).map(|row| (0..width).map(|col| row * width + col).collect());
+        let cols = (0..width).map(|col| (0..width).map(|row| row * width + col).collect());
+        let boxes =
+            (0..width).map(|region| (0..size).filter(|&idx| regions[idx] == region).collect());
+        let units = rows.chain(cols).chain(boxes).collect();
+        Self::from_units(width, regions, units, Vec::new())
+    }
+
+    fn from_units(
+        width: usize,
+        regions: Vec<usize>,
+        units: Vec<Vec<usize>>,
+        moves: Vec<ChessMove>,
+    ) -> Self {
+        let size = width * width;
+        let mut units_of = vec![Vec::new(); size];
+        for (unit, cells) in units.iter().enumerate() {
+            for &idx in cells {
+                units_of[idx].push(unit);
+            }
+        }
+        let peers = (0..size)
+            .map(|idx| {
+                let peers = |kind: usize| {
+                    let unit = &units[units_of[idx][kind]];
+                    unit.iter().copied().filter(|&other| other != idx).collect()
+                };
+                [peers(0), peers(1), peers(2)]
+            })
+            .collect();
+        let neighbors = (0..size)
+            .map(|idx| {
+                let mut neighbors: Vec<usize> = units_of[idx]
+                    .iter()
+                    .flat_map(|&unit| units[unit].iter().copied())
+                    .filter(|&other| other != idx)
+                    .collect();
+                for chess_move in &moves {
+                    neighbors.extend(chess_move.targets(width, idx));
+                }
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                neighbors
+            })
+            .collect();
+        Self(Arc::new(Tables {
+            width,
+            regions,
+            units,
+            units_of,
+            peers,
+            moves,
+            neighbors,
+        }))
+    }
+
+    /// Adds a unit whose cells must all hold different digits, on top of the
+    /// rows, columns and regions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the unit doesn't have as many distinct cells as a
+    /// row.
+    pub fn with_unit(self, mut cells: Vec<usize>) -> Result<Self> {
+        cells.sort_unstable();
+        cells.dedup();
+        if cells.len() != self.width() || cells.iter().any(|&idx| idx >= self.size()) {
+            return Err(Error::IndexError);
+        }
+        let Tables {
+            width,
+            regions,
+            mut units,
+            moves,
+            ..
+        } = Arc::unwrap_or_clone(self.0);
+        units.push(cells);
+        Ok(Self::from_units(width, regions, units, moves))
+    }
+
+    /// Forbids cells a chess move apart from holding the same digit, see
+    /// [`ChessMove`]. These cells don't form units, they only become
+    /// neighbors.
+    #[must_use]
+    pub fn with_chess_move(self, chess_move: ChessMove) -> Self {
+        let Tables {
+            width,
+            regions,
+            units,
+            mut moves,
+            ..
+        } = Arc::unwrap_or_clone(self.0);
+        if !moves.contains(&chess_move) {
+            moves.push(chess_move);
+        }
+        Self::from_units(width, regions, units, moves)
+    }
+
+    /// Adds a set of extra units, see [`ExtraUnits`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the extra units don't fit the grid.
+    pub fn with_extra(self, extra: ExtraUnits) -> Result<Self> {
+        let units = extra.units(&self)?;
+        units.into_iter().try_fold(self, Self::with_unit)
+    }
+
+    /// Adds the extra units named in a comma separated list, such as
+    /// `"diagonals, windoku"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a name is not known, or if the extra units don't
+    /// fit the grid.
+    pub fn with_extras(self, spec: &str) -> Result<Self> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(self, |geometry, name| {
+                geometry.with_extra(ExtraUnits::from_str(name)?)
+            })
+    }
+
+    /// The number of digits, and of cells in every row and column.
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.0.width
+    }
+
+    /// The number of cells in the grid.
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.0.width * self.0.width
+    }
+
+    /// The region (box) that a cell belongs to.
+    #[inline]
+    #[must_use]
+    pub fn region_of(&self, idx: usize) -> usize {
+        self.0.regions[idx]
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn units(&self) -> &[Vec<usize>] {
+        &self.0.units
+    }
+
+    /// The units that a cell belongs to, as indices into [`Geometry::units`].
+    #[inline]
+    #[must_use]
+    pub fn units_of(&self, idx: usize) -> &[usize] {
+        &self.0.units_of[idx]
+    }
+
+    /// The cells that share a unit with a cell, or that are a chess move
+    /// away from it.
+    #[inline]
+    #[must_use]
+    pub fn neighbors(&self, idx: usize) -> &[usize] {
+        &self.0.neighbors[idx]
+    }
+}
+
+impl Shape for Geometry {
+    type Cells = Vec<BitWidth>;
+
+    #[inline]
+    fn width(&self) -> usize {
+        self.0.width
+    }
+
+    #[inline]
+    fn cells(&self, value: BitWidth) -> Self::Cells {
+        vec![value; self.size()]
+    }
+
+    #[inline]
+    fn num_units(&self) -> usize {
+        self.0.units.len()
+    }
+
+    #[inline]
+    fn unit(&self, unit: usize) -> &[usize] {
+        &self.0.units[unit]
+    }
+
+    #[inline]
+    fn units_of(&self, idx: usize) -> &[usize] {
+        &self.0.units_of[idx]
+    }
+
+    #[inline]
+    fn peers(&self, idx: usize, kind: usize) -> &[usize] {
+        &self.0.peers[idx][kind]
+    }
+
+    #[inline]
+    fn neighbors(&self, idx: usize) -> &[usize] {
+        &self.0.neighbors[idx]
+    }
+}
+
+/// Extra units that a puzzle can add on top of rows, columns and regions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraUnits {
+    /// The two main diagonals, as in Sudoku-X.
+    Diagonals,
+    /// The square windows between the boxes, four of them in a 9x9 grid. Only
+    /// for grids with a square width.
+    Windoku,
+    /// The cells at the same position in each region, counting row by row.
+    DisjointGroups,
+}
+
+impl fmt::Display for ExtraUnits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Diagonals => write!(f, "diagonals"),
+            Self::Windoku => write!(f, "windoku"),
+            Self::DisjointGroups => write!(f, "disjoint"),
+        }
+    }
+}
+
+impl FromStr for ExtraUnits {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "diagonals" | "x" => Ok(Self::Diagonals),
+            "windoku" | "hyper" => Ok(Self::Windoku),
+            "disjoint" => Ok(Self::DisjointGroups),
+            _ => Err(Error::ParseError),
+        }
+    }
+}
+
+impl ExtraUnits {
+    fn units(self, geometry: &Geometry) -> Result<Vec<Vec<usize>>> {
+        let width = geometry.width();
+        match self {
+            Self::Diagonals => Ok(vec![
+                (0..width).map(|i| i * width + i).collect(),
+                (0..width).map(|i| i * width + width - 1 - i).collect(),
+            ]),
+            Self::Windoku => {
+                let side = (2..=width)
+                    .find(|&side| side * side == width)
+                    .ok_or(Error::IndexError)?;
+                let starts: Vec<usize> = (1..=width - side).step_by(side + 1).collect();
+                Ok(starts
+                    .iter()
+                    .flat_map(|&top| starts.iter().map(move |&left| (top, left)))
+                    .map(|(top, left)| {
+                        (0..width)
+                            .map(|i| (top + i / side) * width + left + i % side)
+                            .collect()
+                    })
+                    .collect())
+            }
+            Self::DisjointGroups => {
+                let mut groups = vec![Vec::new(); width];
+                let mut seen = vec![0; width];
+                for idx in 0..geometry.size() {
+                    let region = geometry.region_of(idx);
+                    groups[seen[region]].push(idx);
+                    seen[region] += 1;
+                }
+                Ok(groups)
+            }
+        }
+    }
+}
+
+/// Chess moves that a puzzle can forbid between cells with the same digit.
+///
+/// A geometry made with [`Geometry::with_chess_move`] lists these cells among
+/// the neighbors, so [`Sudoku::place`](crate::Sudoku::place) and the search
+/// remove a placed digit from them too. Puzzles on other geometries are not
+/// affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChessMove {
+    /// Cells a knight's move apart, as in anti-knight Sudoku.
+    Knight,
+    /// Cells that touch, including diagonally, as in anti-king Sudoku.
+    King,
+}
+
+impl fmt::Display for ChessMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Knight => write!(f, "anti-knight"),
+            Self::King => write!(f, "anti-king"),
+        }
+    }
+}
+
+impl FromStr for ChessMove {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "anti-knight" | "antiknight" => Ok(Self::Knight),
+            "anti-king" | "antiking" => Ok(Self::King),
+            _ => Err(Error::ParseError),
+        }
+    }
+}
+
+impl ChessMove {
+    const fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Knight => &[
+                (-2, -1),
+                (-2, 1),
+                (-1, -2),
+                (-1, 2),
+                (1, -2),
+                (1, 2),
+                (2, -1),
+                (2, 1),
+            ],
+            Self::King => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
+    }
+
+    /// The cells that are this move away from a cell.
+    fn targets(self, width: usize, idx: usize) -> impl Iterator<Item = usize> {
+        let (row, col) = (idx / width, idx % width);
+        self.offsets().iter().filter_map(move |&(dr, dc)| {
+            let row = row.checked_add_signed(dr).filter(|&row| row < width)?;
+            let col = col.checked_add_signed(dc).filter(|&col| col < width)?;
+            Some(row * width + col)
+        })
+    }
+}
+
+/// Returns true if the cells of the region are orthogonally connected.
+fn is_connected(width: usize, regions: &[usize], region: usize) -> bool {
+    let Some(start) = regions.iter().position(|&r| r == region) else {
+        return false;
+    };
+    let mut seen = vec![false; regions.len()];
+    seen[start] = true;
+    let mut stack = vec![start];
+    let mut num_seen = 1;
+    while let Some(idx) = stack.pop() {
+        let (row, col) = (idx / width, idx % width);
+        let adjacent = [
+            (row > 0).then(|| idx - width),
+            (row + 1 < width).then_some(idx + width),
+            (col > 0).then(|| idx - 1),
+            (col + 1 < width).then_some(idx + 1),
+        ];
+        for next in adjacent.into_iter().flatten() {
+            if regions[next] == region && !seen[next] {
+                seen[next] = true;
+                num_seen += 1;
+                stack.push(next);
+            }
+        }
+    }
+    num_seen == width
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::consts;
+    use rstest::rstest;
+
+    fn regions(layout: &str) -> Vec<usize> {
+        layout
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|region| region as usize)
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_consts() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+
+        assert_eq!(geometry.size(), consts::SIZE);
+        for idx in 0..consts::SIZE {
+            assert_eq!(geometry.neighbors(idx), consts::NEIGHBORS[idx]);
+        }
+        for unit in 0..consts::WIDTH {
+            assert_eq!(geometry.units()[unit], consts::ROWS[unit]);
+            assert_eq!(geometry.units()[consts::WIDTH + unit], consts::COLS[unit]);
+            assert_eq!(
+                geometry.units()[2 * consts::WIDTH + unit],
+                consts::CELLS[unit]
+            );
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(2, 16, 7)]
+    #[case(4, 256, 39)]
+    #[case(5, 625, 64)]
+    fn test_square(
+        #[case] box_size: usize,
+        #[case] size: usize,
+        #[case] num_neighbors: usize,
+    ) -> Result<()> {
+        let geometry = Geometry::square(box_size)?;
+
+        assert_eq!(geometry.size(), size);
+        assert_eq!(geometry.units().len(), 3 * geometry.width());
+        for idx in 0..size {
+            assert_eq!(geometry.units_of(idx).len(), 3);
+            assert_eq!(geometry.neighbors(idx).len(), num_neighbors);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rectangular() -> Result<()> {
+        let geometry = Geometry::rectangular(2, 3)?;
+
+        assert_eq!(geometry.width(), 6);
+        assert_eq!(geometry.units()[12], [0, 1, 2, 6, 7, 8]);
+        assert_eq!(geometry.units()[13], [3, 4, 5, 9, 10, 11]);
+        assert_eq!(geometry.units()[14], [12, 13, 14, 18, 19, 20]);
+        assert_eq!(geometry.units()[17], [27, 28, 29, 33, 34, 35]);
+        assert_eq!(
+            geometry.neighbors(7),
+            [0, 1, 2, 6, 8, 9, 10, 11, 13, 19, 25, 31]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_jigsaw() -> Result<()> {
+        let geometry = Geometry::jigsaw(&regions("0001201122312333"))?;
+
+        assert_eq!(geometry.units()[8], [0, 1, 2, 5]);
+        assert_eq!(geometry.units()[9], [3, 6, 7, 11]);
+        assert_eq!(geometry.region_of(13), 3);
+        assert_eq!(geometry.neighbors(5), [0, 1, 2, 4, 6, 7, 9, 13]);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("000120112231233")]
+    #[case("0001201122312334")]
+    #[case("0001201122322333")]
+    #[case("0101101022322333")]
+    fn test_invalid_jigsaw(#[case] layout: &str) {
+        assert!(Geometry::jigsaw(&regions(layout)).is_err());
+    }
+
+    #[rstest]
+    #[case(ExtraUnits::Diagonals, 2, [0, 10, 20, 30, 40, 50, 60, 70, 80], 26)]
+    #[case(ExtraUnits::Windoku, 4, [10, 11, 12, 19, 20, 21, 28, 29, 30], 20)]
+    #[case(ExtraUnits::DisjointGroups, 9, [0, 3, 6, 27, 30, 33, 54, 57, 60], 24)]
+    fn test_extra_units(
+        #[case] extra: ExtraUnits,
+        #[case] num_extra: usize,
+        #[case] first: [usize; 9],
+        #[case] num_neighbors: usize,
+    ) -> Result<()> {
+        let geometry = Geometry::square(3)?.with_extra(extra)?;
+
+        assert_eq!(geometry.units().len(), 27 + num_extra);
+        assert_eq!(geometry.units()[27], first);
+        assert_eq!(geometry.neighbors(0).len(), num_neighbors);
+        assert_eq!(ExtraUnits::from_str(&extra.to_string())?, extra);
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_extras() -> Result<()> {
+        let geometry = Geometry::square(3)?.with_extras("X, windoku")?;
+        assert_eq!(geometry.units().len(), 33);
+        assert_eq!(geometry.units_of(40).len(), 5);
+        assert_eq!(geometry.units_of(20).len(), 5);
+
+        assert!(Geometry::square(3)?.with_extras("x, knight").is_err());
+        assert!(Geometry::rectangular(2, 3)?.with_extras("windoku").is_err());
+        assert!(Geometry::square(2)?.with_unit(vec![0, 1, 2, 2]).is_err());
+        assert!(Geometry::square(2)?.with_unit(vec![0, 1, 2, 16]).is_err());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(ChessMove::Knight, 40, 28)]
+    #[case(ChessMove::Knight, 0, 20)]
+    #[case(ChessMove::King, 40, 20)]
+    #[case(ChessMove::King, 20, 23)]
+    fn test_chess_moves(
+        #[case] chess_move: ChessMove,
+        #[case] idx: usize,
+        #[case] num_neighbors: usize,
+    ) -> Result<()> {
+        let geometry = Geometry::square(3)?.with_chess_move(chess_move);
+
+        assert_eq!(geometry.units().len(), 27);
+        assert_eq!(geometry.neighbors(idx).len(), num_neighbors);
+        assert_eq!(ChessMove::from_str(&chess_move.to_string())?, chess_move);
+
+        // the moves are kept when units are added
+        let with_diagonals = geometry.clone().with_extra(ExtraUnits::Diagonals)?;
+        for other in geometry.neighbors(idx) {
+            assert!(with_diagonals.neighbors(idx).contains(other));
+        }
+        assert_eq!(geometry.clone().with_chess_move(chess_move), geometry);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(1, 1)]
+    #[case(6, 6)]
+    #[case(1, 4)]
+    #[case(2, 13)]
+    fn test_unsupported(#[case] box_height: usize, #[case] box_width: usize) {
+        assert!(Geometry::rectangular(box_height, box_width).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use core::fmt;
+
+use crate::{
+    board::Board,
+    consts,
+    random::Rng,
+    shape::{Shape, MAX_WIDTH},
+};
+
+/// How the solver picks what to guess when propagation gets stuck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Heuristic {
+    /// The first cell with the fewest candidates, trying digits in ascending
+    /// order.
+    #[default]
+    MinimumRemainingValues,
+    /// The cell with the fewest candidates, breaking ties by the number of
+    /// unsolved neighbors.
+    MrvDegree,
+    /// The two positions of a digit within a unit, even when some cells have
+    /// two candidates, picking the pair whose cells have the fewest candidates.
+    /// Falls back to minimum remaining values when no digit has exactly two
+    /// positions in a unit.
+    Bilocal,
+    /// The first cell with the fewest candidates, trying the digits that appear
+    /// in the fewest neighbors first.
+    LeastConstrainingValue,
+    /// A random cell among those with the fewest candidates, trying digits in a
+    /// random order.
+    Random(u64),
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MinimumRemainingValues => write!(f, "MRV"),
+            Self::MrvDegree => write!(f, "MRV + degree"),
+            Self::Bilocal => write!(f, "Bilocal"),
+            Self::LeastConstrainingValue => write!(f, "LCV"),
+            Self::Random(seed) => write!(f, "Random ({seed})"),
+        }
+    }
+}
+
+/// The placements to try, in order, when the solver has to guess. Exactly one
+/// of them is part of any solution.
+#[derive(Debug, Clone, Copy)]
+pub struct Branch {
+    placements: [(usize, consts::BitWidth); MAX_WIDTH],
+    len: usize,
+}
+
+impl Branch {
+    const fn empty() -> Self {
+        Self {
+            placements: [(0, 0); MAX_WIDTH],
+            len: 0,
+        }
+    }
+
+    const fn push(&mut self, idx: usize, digit: consts::BitWidth) {
+        self.placements[self.len] = (idx, digit);
+        self.len += 1;
+    }
+
+    pub fn as_slice(&self) -> &[(usize, consts::BitWidth)] {
+        &self.placements[..self.len]
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [(usize, consts::BitWidth)] {
+        &mut self.placements[..self.len]
+    }
+
+    /// Every candidate of a single cell, in ascending order.
+    fn cell<B: Board>(board: &B, idx: usize) -> Self {
+        let bitboard = board.candidates(idx);
+        let start = bitboard.trailing_zeros() as consts::BitWidth;
+        let end = consts::NUM_BITS - bitboard.leading_zeros() as consts::BitWidth;
+
+        let mut branch = Self::empty();
+        for digit in (start..end).filter(|&n| bitboard & (1 << n) > 0) {
+            branch.push(idx, digit);
+        }
+        branch
+    }
+}
+
+/// Pick the next branch according to the heuristic, or `None` if there are no
+/// unsolved cells left.
+pub fn next_branch<B: Board>(board: &B, heuristic: Heuristic, rng: &mut Rng) -> Option<Branch> {
+    match heuristic {
+        Heuristic::MinimumRemainingValues => {
+            minimum_remaining_values(board).map(|idx| Branch::cell(board, idx))
+        }
+        Heuristic::MrvDegree => mrv_degree(board).map(|idx| Branch::cell(board, idx)),
+        Heuristic::Bilocal => {
+            let idx = minimum_remaining_values(board)?;
+            bilocal(board).or_else(|| Some(Branch::cell(board, idx)))
+        }
+        Heuristic::LeastConstrainingValue => {
+            let idx = minimum_remaining_values(board)?;
+            let mut branch = Branch::cell(board, idx);
+            // ties keep the ascending digit order
+            branch
+                .as_mut_slice()
+                .sort_unstable_by_key(|&(cell, digit)| {
+                    (num_neighbors_with(board, cell, digit), digit)
+                });
+            Some(branch)
+        }
+        Heuristic::Random(_) => {
+            let idx = random_minimum(board, rng)?;
+            let mut branch = Branch::cell(board, idx);
+            rng.shuffle(branch.as_mut_slice());
+            Some(branch)
+        }
+    }
+}
+
+fn unsolved_with_count<B: Board>(board: &B) -> impl Iterator<Item = (usize, u32)> + '_ {
+    (0..board.shape().size())
+        .filter(|&i| board.digit(i) == 0)
+        .map(|i| (i, board.candidates(i).count_ones()))
+}
+
+fn minimum_remaining_values<B: Board>(board: &B) -> Option<usize> {
+    unsolved_with_count(board)
+        .min_by_key(|&(_, num_possibilities)| num_possibilities)
+        .map(|(idx, _)| idx)
+}
+
+fn mrv_degree<B: Board>(board: &B) -> Option<usize> {
+    unsolved_with_count(board)
+        .min_by_key(|&(idx, num_possibilities)| {
+            let neighbors = board.shape().neighbors(idx);
+            let degree = neighbors
+                .iter()
+                .filter(|&&neighbor| board.digit(neighbor) == 0)
+                .count();
+            (num_possibilities, neighbors.len() - degree)
+        })
+        .map(|(idx, _)| idx)
+}
+
+/// A uniformly random cell among those with the fewest candidates.
+fn random_minimum<B: Board>(board: &B, rng: &mut Rng) -> Option<usize> {
+    let (_, minimum) = unsolved_with_count(board).min_by_key(|&(_, count)| count)?;
+    let mut chosen = None;
+    let mut seen = 0;
+    for (idx, _) in unsolved_with_count(board).filter(|&(_, count)| count == minimum) {
+        seen += 1;
+        if rng.below(seen) == 0 {
+            chosen = Some(idx);
+        }
+    }
+    chosen
+}
+
+/// The digit with exactly two positions in a unit whose two cells have the
+/// fewest candidates between them, the first one found on ties.
+fn bilocal<B: Board>(board: &B) -> Option<Branch> {
+    let shape = board.shape();
+    (0..shape.num_units())
+        .flat_map(|unit| (1..=shape.width()).map(move |digit| (shape.unit(unit), digit)))
+        .filter_map(|(unit, digit)| {
+            let mut positions = unit
+                .iter()
+                .filter(|&&idx| board.candidates(idx) & (1 << digit) > 0);
+            match (positions.next(), positions.next(), positions.next()) {
+                (Some(&first), Some(&second), None) => Some((first, second, digit)),
+                _ => None,
+            }
+        })
+        .min_by_key(|&(first, second, _)| {
+            board.candidates(first).count_ones() + board.candidates(second).count_ones()
+        })
+        .map(|(first, second, digit)| {
+            let mut branch = Branch::empty();
+            branch.push(first, digit);
+            branch.push(second, digit);
+            branch
+        })
+}
+
+fn num_neighbors_with<B: Board>(board: &B, idx: usize, digit: consts::BitWidth) -> usize {
+    board
+        .shape()
+        .neighbors(idx)
+        .iter()
+        .filter(|&&neighbor| board.candidates(neighbor) & (1 << digit) > 0)
+        .count()
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::with_candidates;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[rstest]
+    fn test_heuristics_solve(
+        #[values(
+            Heuristic::MinimumRemainingValues,
+            Heuristic::MrvDegree,
+            Heuristic::Bilocal,
+            Heuristic::LeastConstrainingValue,
+            Heuristic::Random(1),
+            Heuristic::Random(2)
+        )]
+        heuristic: Heuristic,
+    ) -> Result<()> {
+        let sudoku = Sudoku::from_str(
+            "000000036030000050200000000000060800700000400000053000000700210060900000001000000",
+        )?;
+        let (solution, _) = solver::solve_with_heuristic(sudoku, &Pipeline::default(), heuristic)?;
+        assert_eq!(
+            solution.to_string(),
+            "148572936637894152295631748314267895756189423829453671583746219462915387971328564"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bilocal() {
+        // 1 only fits r1c1 or r1c5 in row 1, which is preferred over r9c9 even
+        // though that cell has only two candidates
+        let mut sudoku = with_candidates(&[(0, &[1, 2, 3]), (4, &[1, 2, 3]), (80, &[4, 5])]);
+        for idx in [1, 2, 3, 5, 6, 7, 8] {
+            sudoku.bitboard[idx] &= !(1 << 1);
+        }
+        let branch = next_branch(&sudoku, Heuristic::Bilocal, &mut Rng::new(0));
+
+        assert_eq!(
+            branch.map(|branch| branch.as_slice().to_vec()),
+            Some(vec![(0, 1), (4, 1)])
+        );
+    }
+
+    #[test]
+    fn test_least_constraining_value() {
+        // 2 is a candidate in fewer neighbors of r1c1 than 1 is
+        let mut sudoku = with_candidates(&[(0, &[1, 2])]);
+        for idx in [1, 2, 3] {
+            sudoku.bitboard[idx] &= !(1 << 2);
+        }
+        let branch = next_branch(&sudoku, Heuristic::LeastConstrainingValue, &mut Rng::new(0));
+
+        assert_eq!(
+            branch.map(|branch| branch.as_slice().to_vec()),
+            Some(vec![(0, 2), (0, 1)])
+        );
+    }
+
+    #[test]
+    fn test_mrv_degree() {
+        // r1c1 and r9c9 both have two candidates, but r9c9 has more unsolved
+        // neighbors
+        let mut sudoku = with_candidates(&[(0, &[1, 2]), (80, &[1, 2])]);
+        sudoku.place(1, 3);
+        let branch = next_branch(&sudoku, Heuristic::MrvDegree, &mut Rng::new(0));
+
+        assert_eq!(branch.map(|branch| branch.as_slice()[0].0), Some(80));
+    }
+}
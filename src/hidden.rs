@@ -1,108 +1,87 @@
+use crate::board::Board;
 use crate::consts;
 use crate::error::{Error, Result};
+use crate::shape::Shape;
 use crate::solver::place_and_propagate;
-use crate::Sudoku;
 
 /// Place all hidden singles in the Sudoku.
 ///
 /// # Errors
 ///
 /// Returns an error if the Sudoku is invalid.
-pub fn place_all_hidden_singles(sudoku: &mut Sudoku) -> Result<()> {
-    place_hidden_singles_rows(sudoku)?;
-    place_hidden_singles_cols(sudoku)?;
-    place_hidden_singles_cells(sudoku)
-}
-
-fn place_hidden_singles_rows(sudoku: &mut Sudoku) -> Result<()> {
-    place_hidden_singles(sudoku, &consts::SAME_ROW)
-}
-
-fn place_hidden_singles_cols(sudoku: &mut Sudoku) -> Result<()> {
-    place_hidden_singles(sudoku, &consts::SAME_COL)
-}
-
-fn place_hidden_singles_cells(sudoku: &mut Sudoku) -> Result<()> {
-    place_hidden_singles(sudoku, &consts::SAME_CELL)
+pub fn place_all_hidden_singles<B: Board>(board: &mut B) -> Result<()> {
+    let shape = board.shape().clone();
+    // rows, then columns, then boxes, each visited cell by cell
+    for kind in 0..3 {
+        for idx in 0..shape.size() {
+            let mask = get_hidden_singles_mask(board, shape.peers(idx, kind));
+            place_hidden_single(board, &shape, idx, mask)?;
+        }
+    }
+    for unit in 3 * shape.width()..shape.num_units() {
+        for &idx in shape.unit(unit) {
+            let others = shape.unit(unit).iter().filter(|&&other| other != idx);
+            let mask = get_hidden_singles_mask(board, others);
+            place_hidden_single(board, &shape, idx, mask)?;
+        }
+    }
+    Ok(())
 }
 
-fn place_hidden_singles(
-    sudoku: &mut Sudoku,
-    neighbor_arr: &[[usize; 8]; consts::SIZE],
+/// Places the digit that no other cell in a unit can take, where `mask` holds
+/// the candidates of those other cells.
+fn place_hidden_single<B: Board>(
+    board: &mut B,
+    shape: &B::Shape,
+    idx: usize,
+    mask: consts::BitWidth,
 ) -> Result<()> {
-    neighbor_arr
-        .iter()
-        .enumerate()
-        .try_for_each(|(idx, neighbors)| place_hidden_single(sudoku, idx, neighbors))
-}
-
-fn place_hidden_single(sudoku: &mut Sudoku, idx: usize, neighbors: &[usize; 8]) -> Result<()> {
-    let mask = get_hidden_singles_mask(sudoku, neighbors);
-    let bitboard = sudoku.bitboard[idx];
-    let value = (mask ^ consts::MASK) & bitboard;
+    let bitboard = board.candidates(idx);
+    let value = (mask ^ shape.mask()) & bitboard;
 
-    if value.count_ones() == 1 {
+    if value.is_power_of_two() {
         let digit = value.trailing_zeros() as consts::BitWidth;
-        place_and_propagate(sudoku, idx, digit)
+        place_and_propagate(board, idx, digit)
     } else {
         Ok(())
     }
 }
 
 #[inline]
-fn get_hidden_singles_mask(sudoku: &Sudoku, neighbors: &[usize; 8]) -> consts::BitWidth {
-    neighbors
-        .iter()
-        .map(|&i| sudoku.bitboard[i])
-        .reduce(|a, b| a | b)
-        .unwrap_or(0)
-    // .ok_or(SudokuError::IndexError)
+fn get_hidden_singles_mask<'a, B: Board>(
+    board: &B,
+    cells: impl IntoIterator<Item = &'a usize>,
+) -> consts::BitWidth {
+    cells
+        .into_iter()
+        .map(|&i| board.candidates(i))
+        .fold(0, |a, b| a | b)
 }
 
-pub fn check_all_hidden_zeroes(sudoku: &Sudoku) -> Result<()> {
-    check_hidden_zeroes_rows(sudoku)?;
-    check_hidden_zeroes_cols(sudoku)?;
-    check_hidden_zeroes_cells(sudoku)
-}
-
-fn check_hidden_zeroes_rows(sudoku: &Sudoku) -> Result<()> {
-    validate_hidden_zeroes(sudoku, &consts::ROWS)
-}
-
-fn check_hidden_zeroes_cols(sudoku: &Sudoku) -> Result<()> {
-    validate_hidden_zeroes(sudoku, &consts::COLS)
-}
-
-fn check_hidden_zeroes_cells(sudoku: &Sudoku) -> Result<()> {
-    validate_hidden_zeroes(sudoku, &consts::CELLS)
-}
-
-fn validate_hidden_zeroes(
-    sudoku: &Sudoku,
-    neighbor_arr: &[[usize; consts::WIDTH]; consts::WIDTH],
-) -> Result<()> {
-    neighbor_arr
-        .iter()
-        .try_for_each(|neighbors| validate_mask(sudoku, neighbors))
+/// Fails if a digit has no position left in a unit.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn check_all_hidden_zeroes<B: Board>(board: &B) -> Result<()> {
+    let shape = board.shape();
+    (0..shape.num_units()).try_for_each(|unit| validate_mask(board, shape.unit(unit)))
 }
 
 #[inline]
-fn validate_mask(sudoku: &Sudoku, neighbors: &[usize; consts::WIDTH]) -> Result<()> {
-    if get_hidden_zeroes_mask(sudoku, neighbors) < consts::MASK {
-        Err(Error::from(sudoku))
+fn validate_mask<B: Board>(board: &B, unit: &[usize]) -> Result<()> {
+    if get_hidden_zeroes_mask(board, unit) < board.shape().mask() {
+        Err(Error::NoSolution)
     } else {
         Ok(())
     }
 }
 
 #[inline]
-fn get_hidden_zeroes_mask(sudoku: &Sudoku, neighbors: &[usize; consts::WIDTH]) -> consts::BitWidth {
-    neighbors
-        .iter()
-        .map(|&i| (sudoku.bitboard[i], sudoku.digits[i]))
-        .map(|(bitboard, digit)| bitboard | (1 << digit))
-        .reduce(|a, b| a | b)
-        .unwrap_or(0)
+fn get_hidden_zeroes_mask<B: Board>(board: &B, unit: &[usize]) -> consts::BitWidth {
+    unit.iter()
+        .map(|&i| board.candidates(i) | (1 << board.digit(i)))
+        .fold(0, |a, b| a | b)
 }
 
 #[cfg(test)]
@@ -0,0 +1,377 @@
+use crate::{
+    board::Board,
+    constraint::{options, parse_cell, restrict, Constraint, Mask},
+    geometry::Geometry,
+    technique::Progress,
+    Error, Result,
+};
+
+/// A killer cage: its cells hold different digits that add up to `sum`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cage {
+    pub sum: u32,
+    pub cells: Vec<usize>,
+}
+
+impl Cage {
+    /// A cage over the given cells.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cage is empty, has a cell twice, or has a cell
+    /// outside of the grid.
+    pub fn new(geometry: &Geometry, sum: u32, cells: Vec<usize>) -> Result<Self> {
+        let mut sorted = cells.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if cells.is_empty()
+            || sorted.len() != cells.len()
+            || cells.iter().any(|&idx| idx >= geometry.size())
+        {
+            return Err(Error::IndexError);
+        }
+        Ok(Self { sum, cells })
+    }
+
+    /// Parses a cage written as its sum and its cells, such as
+    /// `15: r1c1 r1c2 r2c1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line is malformed, or if the cage is not valid
+    /// for the grid.
+    pub fn parse(geometry: &Geometry, line: &str) -> Result<Self> {
+        let (sum, cells) = line.split_once(':').ok_or(Error::ParseError)?;
+        let sum = sum.trim().parse().ok().ok_or(Error::ParseError)?;
+        let cells = cells
+            .split_whitespace()
+            .map(|cell| parse_cell(geometry, cell))
+            .collect::<Result<_>>()?;
+        Self::new(geometry, sum, cells)
+    }
+}
+
+/// Parses a killer puzzle with one cage per line, see [`Cage::parse`]. Empty
+/// lines and lines starting with `#` are skipped.
+///
+/// # Errors
+///
+/// Returns an error if a cage is malformed.
+pub fn parse_cages(geometry: &Geometry, s: &str) -> Result<Vec<Cage>> {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| Cage::parse(geometry, line))
+        .collect()
+}
+
+/// The sum of the `count` largest digits in a mask.
+fn largest_sum(mut mask: Mask, count: usize) -> u32 {
+    let mut sum = 0;
+    for _ in 0..count {
+        if mask == 0 {
+            break;
+        }
+        let digit = Mask::BITS - 1 - mask.leading_zeros();
+        sum += digit;
+        mask &= !(1 << digit);
+    }
+    sum
+}
+
+/// Collects every set of `count` digits from `allowed` that adds up to `sum`.
+fn combinations(allowed: Mask, count: usize, sum: u32, chosen: Mask, found: &mut Vec<Mask>) {
+    if count == 0 {
+        if sum == 0 {
+            found.push(chosen);
+        }
+        return;
+    }
+    let mut rest = allowed;
+    while rest != 0 {
+        let digit = rest.trailing_zeros();
+        rest &= rest - 1;
+        // the digits only get larger, and the remaining ones only fewer
+        if digit * count as u32 > sum || largest_sum(rest | 1 << digit, count) < sum {
+            break;
+        }
+        combinations(rest, count - 1, sum - digit, chosen | 1 << digit, found);
+    }
+}
+
+impl<B: Board> Constraint<B> for Cage {
+    fn propagate(&self, board: &mut B) -> Result<Progress> {
+        let mut placed: Mask = 0;
+        let mut total = 0;
+        for &idx in &self.cells {
+            let digit = board.digit(idx);
+            if digit != 0 {
+                if placed & (1 << digit) != 0 {
+                    return Err(Error::NoSolution);
+                }
+                placed |= 1 << digit;
+                total += digit as u32;
+            }
+        }
+        let remaining = self.sum.checked_sub(total).ok_or(Error::NoSolution)?;
+        let open: Vec<usize> = self
+            .cells
+            .iter()
+            .copied()
+            .filter(|&idx| board.digit(idx) == 0)
+            .collect();
+        if open.is_empty() {
+            return if remaining == 0 {
+                Ok(Progress::Unchanged)
+            } else {
+                Err(Error::NoSolution)
+            };
+        }
+
+        let allowed = open.iter().fold(0, |mask, &idx| mask | options(board, idx)) & !placed;
+        let mut found = Vec::new();
+        combinations(allowed, open.len(), remaining, 0, &mut found);
+        let mut supported = vec![0; open.len()];
+        for combination in found {
+            if open
+                .iter()
+                .all(|&idx| options(board, idx) & combination != 0)
+            {
+                for (mask, &idx) in supported.iter_mut().zip(&open) {
+                    *mask |= options(board, idx) & combination;
+                }
+            }
+        }
+
+        let mut progress = Progress::Unchanged;
+        for (&idx, &mask) in open.iter().zip(&supported) {
+            if restrict(board, idx, mask)? == Progress::Changed {
+                progress = Progress::Changed;
+            }
+        }
+        Ok(progress)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::{solver, Pipeline, Sudoku};
+    use rstest::rstest;
+
+    /// Solves a puzzle under its cages. With `all_solutions`, this fails
+    /// unless the solution is unique.
+    fn solve_cages(puzzle: Sudoku<Geometry>, cages: Vec<Cage>) -> Result<Sudoku<Geometry>> {
+        let mut pipeline = Pipeline::default();
+        pipeline.extend(
+            cages
+                .into_iter()
+                .map(|cage| Box::new(cage) as Box<dyn Constraint<_>>),
+        );
+        Ok(solver::solve_with(puzzle, &pipeline)?.0)
+    }
+
+    #[rstest]
+    #[case(0b11_1111_1110, 2, 10, vec![0b10_0000_0010, 0b1_0000_0100, 0b1000_1000, 0b101_0000])]
+    #[case(0b11_1111_1110, 3, 6, vec![0b1110])]
+    #[case(0b11_1111_1110, 3, 24, vec![0b11_1000_0000])]
+    #[case(0b11_1111_1110, 9, 45, vec![0b11_1111_1110])]
+    #[case(0b11_1111_1110, 2, 18, vec![])]
+    #[case(0b1010_1010, 2, 9, vec![])]
+    fn test_combinations(
+        #[case] allowed: Mask,
+        #[case] count: usize,
+        #[case] sum: u32,
+        #[case] expected: Vec<Mask>,
+    ) {
+        let mut found = Vec::new();
+        combinations(allowed, count, sum, 0, &mut found);
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_propagate() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        // 3 in two cells is 1 + 2
+        let cage = Cage::parse(&geometry, "3: r1c1 r1c2")?;
+
+        assert_eq!(cage.propagate(&mut sudoku)?, Progress::Changed);
+        assert_eq!(sudoku.candidates(0), 0b110);
+        assert_eq!(sudoku.candidates(1), 0b110);
+        assert_eq!(cage.propagate(&mut sudoku)?, Progress::Unchanged);
+
+        sudoku.place(0, 2);
+        cage.propagate(&mut sudoku)?;
+        assert_eq!(sudoku.candidates(1), 0b10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_violated() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let cage = Cage::parse(&geometry, "10: r1c1 r2c2")?;
+
+        let mut too_large =
+            Sudoku::parse(&geometry, &format!("3{}4{}", ".".repeat(9), ".".repeat(70)))?;
+        assert!(cage.propagate(&mut too_large).is_err());
+        let mut unreachable = Sudoku::parse(&geometry, &format!("9{}", ".".repeat(80)))?;
+        unreachable.keep_candidates(10, !(1 << 1));
+        assert!(cage.propagate(&mut unreachable).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_killer() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let cages = parse_cages(
+            &geometry,
+            "
+        # a killer without givens
+        10: r1c1 r2c1
+        12: r1c2 r1c3
+        15: r1c4 r1c5
+        9: r1c6 r1c7
+        9: r1c8 r1c9 r2c9
+        15: r2c2 r3c2 r3c3
+        21: r2c3 r2c4 r3c4
+        9: r2c5 r3c5 r2c6
+        20: r2c7 r2c8 r3c7
+        10: r3c1 r4c1
+        4: r3c6 r4c6
+        19: r3c8 r3c9 r4c8
+        5: r4c2 r4c3
+        8: r4c4 r5c4
+        9: r4c5 r5c5
+        13: r4c7 r5c7 r6c7
+        13: r4c9 r5c9 r6c9
+        11: r5c1 r5c2
+        18: r5c3 r6c3 r7c3
+        15: r5c6 r6c6
+        11: r5c8 r6c8
+        11: r6c1 r7c1 r7c2
+        4: r6c2
+        7: r6c4 r7c4
+        16: r6c5 r7c5
+        20: r7c6 r8c6 r9c6
+        15: r7c7 r7c8 r8c7
+        15: r7c9 r8c9 r8c8
+        10: r8c1 r9c1
+        12: r8c2 r9c2
+        18: r8c3 r9c3 r9c4
+        6: r8c4 r8c5 r9c5
+        6: r9c7 r9c8
+        9: r9c9
+        ",
+        )?;
+        assert_eq!(cages.len(), 34);
+        let solution = solve_cages(Sudoku::new(&geometry), cages)?;
+
+        assert_eq!(
+            solution.to_string(),
+            "693784512487512936125963874932651487568247391741398625319475268856129743274836159"
+        );
+        let (unconstrained, _) = solver::solve_random(Sudoku::new(&geometry), 0)?;
+        assert_ne!(unconstrained.to_string(), solution.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_published_killer() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let cages = parse_cages(
+            &geometry,
+            "
+            # the example puzzle of the Killer sudoku article on Wikipedia
+            3: r1c1 r1c2
+            15: r1c3 r1c4 r1c5
+            22: r1c6 r2c5 r2c6 r3c5
+            4: r1c7 r2c7
+            16: r1c8 r2c8
+            15: r1c9 r2c9 r3c9 r4c9
+            25: r2c1 r2c2 r3c1 r3c2
+            17: r2c3 r2c4
+            9: r3c3 r3c4 r4c4
+            8: r3c6 r4c6 r5c6
+            20: r3c7 r3c8 r4c7
+            6: r4c1 r5c1
+            14: r4c2 r4c3
+            17: r4c5 r5c5 r6c5
+            17: r4c8 r5c7 r5c8
+            13: r5c2 r5c3 r6c2
+            20: r5c4 r6c4 r7c4
+            12: r5c9 r6c9
+            27: r6c1 r7c1 r8c1 r9c1
+            6: r6c3 r7c2 r7c3
+            20: r6c6 r7c6 r7c7
+            6: r6c7 r6c8
+            10: r7c5 r8c4 r8c5 r9c4
+            14: r7c8 r7c9 r8c8 r8c9
+            8: r8c2 r9c2
+            16: r8c3 r9c3
+            15: r8c6 r8c7
+            13: r9c5 r9c6 r9c7
+            17: r9c8 r9c9
+            ",
+        )?;
+        assert_eq!(cages.iter().map(|cage| cage.sum).sum::<u32>(), 405);
+        // with `all_solutions` this also proves that the solution is unique
+        let solution = solve_cages(Sudoku::new(&geometry), cages)?;
+
+        assert_eq!(
+            solution.to_string(),
+            "215647398368952174794381652586274931142593867973816425821739546659428713437165289"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_killer_with_givens() -> Result<()> {
+        let geometry = Geometry::rectangular(2, 3)?;
+        let cages = parse_cages(
+            &geometry,
+            "
+            12: r1c1 r1c2 r1c3
+            14: r1c4 r1c5 r2c5 r1c6
+            10: r2c1 r2c2 r3c1 r2c3
+            10: r2c4 r3c4 r4c4
+            13: r2c6 r3c6 r3c5
+            12: r3c2 r3c3 r4c3
+            8: r4c1 r4c2
+            11: r4c5 r4c6 r5c5
+            3: r5c1 r5c2
+            12: r5c3 r5c4 r6c4 r6c5
+            7: r5c6 r6c6
+            9: r6c1 r6c2
+            5: r6c3
+            ",
+        )?;
+        let puzzle = Sudoku::parse(&geometry, "............1.....3.................")?;
+        let solution = solve_cages(puzzle, cages.clone())?;
+
+        assert_eq!(solution.to_string(), "561432423156146325352641214563635214");
+        // the givens are needed: the cages alone leave more than one solution
+        if cfg!(feature = "all_solutions") {
+            assert!(matches!(
+                solve_cages(Sudoku::new(&geometry), cages),
+                Err(Error::MultipleSolutions)
+            ));
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("r1c1 r1c2")]
+    #[case("x: r1c1")]
+    #[case("3: r1c1 r1c1")]
+    #[case("3: r0c1")]
+    #[case("3: r1c10")]
+    #[case("3: a1")]
+    #[case("3:")]
+    fn test_parse_errors(#[case] line: &str) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        assert!(Cage::parse(&geometry, line).is_err());
+        Ok(())
+    }
+}
@@ -1,15 +1,113 @@
+//! A Sudoku solver.
+//!
+//! Without the default `std` feature, the core solver
+//! (`Sudoku`, the singles and pointing techniques, and the search) builds
+//! under `no_std` and solves without allocating.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "std")]
+mod aic;
+#[cfg(feature = "std")]
+mod als;
+mod board;
+// most helpers are only used by the techniques that need `std`
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+mod candidate;
+#[cfg(feature = "std")]
+mod chains;
+#[cfg(feature = "std")]
+mod coloring;
+#[cfg(feature = "std")]
+mod compact;
+#[cfg(feature = "std")]
+mod constraint;
 mod consts;
+#[cfg(feature = "std")]
 mod debug;
+#[cfg(feature = "std")]
+mod dots;
 mod error;
+#[cfg(feature = "std")]
+mod forcing;
+#[cfg(feature = "std")]
+mod geometry;
+mod heuristic;
 mod hidden;
+#[cfg(feature = "std")]
+mod killer;
+#[cfg(feature = "std")]
+mod lines;
+mod pipeline;
+mod random;
+mod shape;
 pub mod solver;
+mod stats;
 mod sudoku;
+mod technique;
 mod triples;
+#[cfg(feature = "std")]
+mod uniqueness;
 mod visible;
+#[cfg(feature = "std")]
+mod wings;
 
+#[cfg(feature = "std")]
+pub use crate::aic::{apply_aic, find_aics, find_xy_chains, Aic, AicKind};
+#[cfg(feature = "std")]
+pub use crate::als::{
+    apply_als_step, find_almost_locked_sets, find_als_steps, find_als_xy_wings, find_als_xz,
+    find_sue_de_coq, Als, AlsKind, AlsStep,
+};
+pub use crate::board::Board;
+pub use crate::candidate::{Candidate, CellName};
+#[cfg(feature = "std")]
+pub use crate::chains::{apply_x_chain, find_x_chains, Chain, LinkKind, XChain, XChainKind};
+#[cfg(feature = "std")]
+pub use crate::coloring::{apply_coloring, find_colorings, Coloring, ColoringKind};
+#[cfg(feature = "std")]
+pub use crate::compact::CompactSudoku;
+#[cfg(feature = "std")]
+pub use crate::constraint::{digits, options, parse_cell, restrict, supported, Constraint, Mask};
+#[cfg(feature = "std")]
+pub use crate::dots::{Dot, Dots, Edge};
 pub use crate::error::{Error, Result};
+#[cfg(feature = "std")]
+pub use crate::forcing::{
+    apply_forcing_chain, find_cell_forcing_chains, find_digit_forcing_chains, find_forcing_chains,
+    find_nishio, find_unit_forcing_chains, Assumption, ForcingChain, ForcingKind,
+};
+#[cfg(feature = "std")]
+pub use crate::geometry::{ChessMove, ExtraUnits, Geometry};
+pub use crate::heuristic::Heuristic;
 pub use crate::hidden::place_all_hidden_singles;
-pub use crate::solver::{check_constraints, solve};
+#[cfg(feature = "std")]
+pub use crate::killer::{parse_cages, Cage};
+#[cfg(feature = "std")]
+pub use crate::lines::{parse_lines, Arrow, Palindrome, Renban, Thermometer, Whisper};
+pub use crate::pipeline::Pipeline;
+pub use crate::shape::{Boxes, Classic, Shape};
+pub use crate::solver::{
+    check_constraints, solve, solve_logically, solve_random, solve_with, solve_with_heuristic,
+    Status,
+};
+pub use crate::stats::{SolveStats, TechniqueStats};
 pub use crate::sudoku::Sudoku;
+pub use crate::technique::{
+    apply_counting, HiddenSingles, HiddenZeroes, Progress, Technique, Triples, VisibleDoubles,
+    VisibleSingles,
+};
+#[cfg(feature = "std")]
+pub use crate::technique::{
+    Aics, AlmostLockedSets, Colorings, DeadlyPatterns, ForcingChains, Wings, XChains,
+};
 pub use crate::triples::check_triples;
+#[cfg(feature = "std")]
+pub use crate::uniqueness::{
+    apply_deadly_pattern, find_bug_plus_one, find_deadly_patterns, find_unique_rectangles,
+    DeadlyPattern, DeadlyPatternKind, Uniqueness,
+};
 pub use crate::visible::{check_all_visible_doubles, place_all_visible_singles};
+#[cfg(feature = "std")]
+pub use crate::wings::{
+    apply_wing, find_w_wings, find_wings, find_xy_wings, find_xyz_wings, Pivot, Wing, WingKind,
+};
@@ -0,0 +1,543 @@
+use crate::{
+    board::Board,
+    constraint::{options, parse_cell, restrict, supported, Constraint, Mask},
+    geometry::Geometry,
+    shape::Shape,
+    technique::Progress,
+    Error, Result,
+};
+
+/// The smallest difference between neighbors on a German whispers line.
+const WHISPER_DIFFERENCE: u8 = 5;
+
+/// A thermometer: the digits strictly increase from the bulb, which is the
+/// first cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thermometer {
+    cells: Vec<usize>,
+}
+
+/// An arrow: the digit in the circle is the sum of the digits along the
+/// path. Digits on the path may repeat unless they share a unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arrow {
+    circle: usize,
+    path: Vec<usize>,
+}
+
+/// A palindrome: the line reads the same from both ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palindrome {
+    cells: Vec<usize>,
+}
+
+/// A renban line: the cells hold a set of consecutive digits, in any order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Renban {
+    cells: Vec<usize>,
+}
+
+/// A German whispers line: neighbors along the line differ by at least 5.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Whisper {
+    cells: Vec<usize>,
+}
+
+/// Checks that a line has at least two cells, all of them different and
+/// inside the grid.
+fn check_line(geometry: &Geometry, cells: &[usize]) -> Result<()> {
+    let mut sorted = cells.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if cells.len() < 2
+        || sorted.len() != cells.len()
+        || cells.iter().any(|&idx| idx >= geometry.size())
+    {
+        return Err(Error::IndexError);
+    }
+    Ok(())
+}
+
+impl Thermometer {
+    /// A thermometer over the given cells, starting at the bulb.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line has fewer than two cells, has a cell
+    /// twice, or has a cell outside of the grid.
+    pub fn new(geometry: &Geometry, cells: Vec<usize>) -> Result<Self> {
+        check_line(geometry, &cells)?;
+        Ok(Self { cells })
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+}
+
+impl Arrow {
+    /// An arrow from the circle along the given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is empty, if a cell is on the arrow
+    /// twice, or if a cell is outside of the grid.
+    pub fn new(geometry: &Geometry, circle: usize, path: Vec<usize>) -> Result<Self> {
+        let cells: Vec<usize> = std::iter::once(circle)
+            .chain(path.iter().copied())
+            .collect();
+        check_line(geometry, &cells)?;
+        Ok(Self { circle, path })
+    }
+
+    #[must_use]
+    pub const fn circle(&self) -> usize {
+        self.circle
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+}
+
+impl Palindrome {
+    /// A palindrome over the given cells.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line has fewer than two cells, has a cell
+    /// twice, or has a cell outside of the grid.
+    pub fn new(geometry: &Geometry, cells: Vec<usize>) -> Result<Self> {
+        check_line(geometry, &cells)?;
+        Ok(Self { cells })
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+}
+
+impl Renban {
+    /// A renban line over the given cells.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line has fewer than two cells, has a cell
+    /// twice, has a cell outside of the grid, or has more cells than there
+    /// are digits.
+    pub fn new(geometry: &Geometry, cells: Vec<usize>) -> Result<Self> {
+        check_line(geometry, &cells)?;
+        if cells.len() > geometry.width() {
+            return Err(Error::IndexError);
+        }
+        Ok(Self { cells })
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+}
+
+impl Whisper {
+    /// A German whispers line over the given cells.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line has fewer than two cells, has a cell
+    /// twice, or has a cell outside of the grid.
+    pub fn new(geometry: &Geometry, cells: Vec<usize>) -> Result<Self> {
+        check_line(geometry, &cells)?;
+        Ok(Self { cells })
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+}
+
+/// Parses a puzzle's lines, one per row of text such as
+/// `thermo: r1c1 r1c2 r1c3`.
+///
+/// The kinds are `thermo`, `arrow` (the circle comes first), `palindrome`,
+/// `renban` and `whisper`. Empty lines and lines starting with `#` are
+/// skipped.
+///
+/// # Errors
+///
+/// Returns an error if a line is malformed, of an unknown kind, or not valid
+/// for the grid.
+pub fn parse_lines<B: Board>(geometry: &Geometry, s: &str) -> Result<Vec<Box<dyn Constraint<B>>>> {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| parse_line(geometry, line))
+        .collect()
+}
+
+fn parse_line<B: Board>(geometry: &Geometry, line: &str) -> Result<Box<dyn Constraint<B>>> {
+    let (kind, cells) = line.split_once(':').ok_or(Error::ParseError)?;
+    let cells: Vec<usize> = cells
+        .split_whitespace()
+        .map(|cell| parse_cell(geometry, cell))
+        .collect::<Result<_>>()?;
+    Ok(match kind.trim().to_ascii_lowercase().as_str() {
+        "thermo" | "thermometer" => Box::new(Thermometer::new(geometry, cells)?),
+        "arrow" => {
+            let (&circle, path) = cells.split_first().ok_or(Error::IndexError)?;
+            Box::new(Arrow::new(geometry, circle, path.to_vec())?)
+        }
+        "palindrome" => Box::new(Palindrome::new(geometry, cells)?),
+        "renban" => Box::new(Renban::new(geometry, cells)?),
+        "whisper" | "whispers" => Box::new(Whisper::new(geometry, cells)?),
+        _ => return Err(Error::ParseError),
+    })
+}
+
+/// The digits from `low` to `high`, both included.
+fn range(low: u32, high: u32) -> Mask {
+    let low = low.max(1);
+    let high = high.min(Mask::BITS - 1);
+    if low > high {
+        return 0;
+    }
+    (Mask::MAX >> (Mask::BITS - 1 - high)) & !((1 << low) - 1)
+}
+
+const fn lowest(mask: Mask) -> u32 {
+    mask.trailing_zeros()
+}
+
+const fn highest(mask: Mask) -> u32 {
+    Mask::BITS - 1 - mask.leading_zeros()
+}
+
+/// Prunes a line where every pair of neighbors has to be related, by
+/// passing over it in both directions.
+fn propagate_path<B: Board>(
+    board: &mut B,
+    cells: &[usize],
+    related: impl Fn(u8, u8) -> bool,
+) -> Result<Progress> {
+    let mut masks: Vec<Mask> = cells.iter().map(|&idx| options(board, idx)).collect();
+    for i in 1..masks.len() {
+        masks[i] = supported(masks[i - 1], masks[i], &related);
+    }
+    for i in (0..masks.len() - 1).rev() {
+        masks[i] = supported(masks[i + 1], masks[i], |a, b| related(b, a));
+    }
+    restrict_all(board, cells, &masks)
+}
+
+fn restrict_all<B: Board>(board: &mut B, cells: &[usize], masks: &[Mask]) -> Result<Progress> {
+    let mut changed = false;
+    for (&idx, &mask) in cells.iter().zip(masks) {
+        changed |= restrict(board, idx, mask)? == Progress::Changed;
+    }
+    Ok(changed.into())
+}
+
+impl<B: Board> Constraint<B> for Thermometer {
+    fn propagate(&self, board: &mut B) -> Result<Progress> {
+        propagate_path(board, &self.cells, |a, b| a < b)
+    }
+}
+
+impl<B: Board> Constraint<B> for Whisper {
+    fn propagate(&self, board: &mut B) -> Result<Progress> {
+        propagate_path(board, &self.cells, |a, b| {
+            a.abs_diff(b) >= WHISPER_DIFFERENCE
+        })
+    }
+}
+
+impl<B: Board> Constraint<B> for Palindrome {
+    fn propagate(&self, board: &mut B) -> Result<Progress> {
+        let mut changed = false;
+        for (&first, &second) in self.cells.iter().zip(self.cells.iter().rev()) {
+            let mask = options(board, first) & options(board, second);
+            changed |= restrict(board, first, mask)? == Progress::Changed;
+        }
+        Ok(changed.into())
+    }
+}
+
+impl<B: Board> Constraint<B> for Arrow {
+    fn propagate(&self, board: &mut B) -> Result<Progress> {
+        let masks: Vec<Mask> = self.path.iter().map(|&idx| options(board, idx)).collect();
+        if masks.contains(&0) {
+            return Err(Error::NoSolution);
+        }
+        let low: u32 = masks.iter().map(|&mask| lowest(mask)).sum();
+        let high: u32 = masks.iter().map(|&mask| highest(mask)).sum();
+        let mut changed = restrict(board, self.circle, range(low, high))? == Progress::Changed;
+
+        // each cell takes what the circle leaves after the rest of the path
+        let circle = options(board, self.circle);
+        let allowed: Vec<Mask> = masks
+            .iter()
+            .map(|&mask| {
+                let rest_low = low - lowest(mask);
+                let rest_high = high - highest(mask);
+                range(
+                    lowest(circle).saturating_sub(rest_high),
+                    highest(circle).saturating_sub(rest_low),
+                )
+            })
+            .collect();
+        changed |= restrict_all(board, &self.path, &allowed)? == Progress::Changed;
+        Ok(changed.into())
+    }
+}
+
+impl<B: Board> Constraint<B> for Renban {
+    fn propagate(&self, board: &mut B) -> Result<Progress> {
+        let mut placed: Mask = 0;
+        for &idx in &self.cells {
+            let digit = board.digit(idx);
+            if digit != 0 {
+                if placed & (1 << digit) != 0 {
+                    return Err(Error::NoSolution);
+                }
+                placed |= 1 << digit;
+            }
+        }
+        // a run of consecutive digits fits if every cell can take one of them
+        let length = self.cells.len() as u32;
+        let width = board.shape().width() as u32;
+        let allowed = (1..=width + 1 - length)
+            .map(|low| range(low, low + length - 1))
+            .filter(|&run| {
+                placed & !run == 0 && self.cells.iter().all(|&idx| options(board, idx) & run != 0)
+            })
+            .fold(0, |mask, run| mask | run);
+
+        let mut changed = false;
+        for &idx in &self.cells {
+            let mask = if board.digit(idx) == 0 {
+                allowed & !placed
+            } else {
+                allowed
+            };
+            changed |= restrict(board, idx, mask)? == Progress::Changed;
+        }
+        Ok(changed.into())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::{constraint::digits, solver, Pipeline, Sudoku};
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    fn candidates(sudoku: &Sudoku<Geometry>, cells: &[usize]) -> Vec<Vec<u8>> {
+        cells
+            .iter()
+            .map(|&idx| digits(options(sudoku, idx)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(range(1, 3), 0b1110);
+        assert_eq!(range(4, 4), 0b1_0000);
+        assert_eq!(range(5, 4), 0);
+        assert_eq!(range(30, 100), 0b11 << 30);
+    }
+
+    #[rstest]
+    #[case("thermo: r1c1 r1c2 r1c3", vec![vec![1, 2, 3, 4, 5, 6, 7], vec![2, 3, 4, 5, 6, 7, 8], vec![3, 4, 5, 6, 7, 8, 9]])]
+    #[case("whisper: r1c1 r2c1", vec![vec![1, 2, 3, 4, 6, 7, 8, 9], vec![1, 2, 3, 4, 6, 7, 8, 9]])]
+    #[case("arrow: r1c1 r2c2 r3c3 r4c4", vec![vec![3, 4, 5, 6, 7, 8, 9], vec![1, 2, 3, 4, 5, 6, 7], vec![1, 2, 3, 4, 5, 6, 7], vec![1, 2, 3, 4, 5, 6, 7]])]
+    #[case("renban: r1c1 r1c2", vec![vec![1, 2, 3, 4, 5, 6, 7, 8, 9], vec![1, 2, 3, 4, 5, 6, 7, 8, 9]])]
+    fn test_empty_grid(#[case] line: &str, #[case] expected: Vec<Vec<u8>>) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        let constraint = parse_line(&geometry, line)?;
+        let cells: Vec<usize> = line
+            .split_whitespace()
+            .skip(1)
+            .map(|cell| parse_cell(&geometry, cell))
+            .collect::<Result<_>>()?;
+
+        constraint.propagate(&mut sudoku)?;
+        assert_eq!(candidates(&sudoku, &cells), expected);
+        assert_eq!(constraint.propagate(&mut sudoku)?, Progress::Unchanged);
+        Ok(())
+    }
+
+    #[test]
+    fn test_thermometer() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let cells = [0, 1, 2, 3];
+        let mut sudoku = Sudoku::new(&geometry);
+        sudoku.place(2, 5);
+
+        Thermometer::new(&geometry, cells.to_vec())?.propagate(&mut sudoku)?;
+        assert_eq!(
+            candidates(&sudoku, &cells),
+            [vec![1, 2, 3], vec![2, 3, 4], vec![5], vec![6, 7, 8, 9]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrow() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        sudoku.place(0, 4);
+        let arrow = Arrow::new(&geometry, 0, vec![10, 20])?;
+
+        arrow.propagate(&mut sudoku)?;
+        assert_eq!(
+            candidates(&sudoku, &[10, 20]),
+            [vec![1, 2, 3], vec![1, 2, 3]]
+        );
+        sudoku.place(10, 3);
+        arrow.propagate(&mut sudoku)?;
+        assert_eq!(candidates(&sudoku, &[20]), [vec![1]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_palindrome() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        sudoku.place(0, 7);
+        sudoku.keep_candidates(12, !(1 << 3));
+
+        Palindrome::new(&geometry, vec![0, 1, 12, 13])?.propagate(&mut sudoku)?;
+        assert_eq!(candidates(&sudoku, &[13]), [vec![7]]);
+        assert_eq!(candidates(&sudoku, &[1]), [vec![1, 2, 4, 5, 6, 8, 9]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_renban() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        sudoku.place(0, 2);
+        sudoku.place(40, 4);
+
+        Renban::new(&geometry, vec![0, 40, 80])?.propagate(&mut sudoku)?;
+        assert_eq!(candidates(&sudoku, &[80]), [vec![3]]);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("thermo: r1c1 r1c2", &[(0, 2), (1, 1)])]
+    #[case("whisper: r1c1 r2c1", &[(0, 1), (9, 3)])]
+    #[case("arrow: r1c1 r2c2 r3c3", &[(0, 1)])]
+    #[case("palindrome: r1c1 r2c4 r3c7", &[(0, 1), (24, 2)])]
+    #[case("renban: r1c1 r3c4", &[(0, 1), (21, 5)])]
+    fn test_violated(#[case] line: &str, #[case] givens: &[(usize, usize)]) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let constraint = parse_line(&geometry, line)?;
+        let mut sudoku = Sudoku::new(&geometry);
+        for &(idx, digit) in givens {
+            sudoku.place(idx, digit);
+        }
+
+        assert!(constraint.propagate(&mut sudoku).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let lines = parse_lines(
+            &geometry,
+            "
+            thermo: r6c4 r5c5
+            thermo: r7c2 r8c2 r8c3
+            thermo: r4c3 r4c2 r5c3
+            thermo: r4c8 r5c8
+            whisper: r9c4 r9c5 r8c6 r7c7 r6c6
+            renban: r6c9 r7c8 r8c7
+            arrow: r3c5 r4c4
+            arrow: r5c7 r6c8 r5c9
+            renban: r2c1 r3c2 r4c2
+            arrow: r5c3 r4c4 r4c3
+            palindrome: r9c3 r8c4 r7c4
+            arrow: r4c9 r3c9 r2c8
+            whisper: r9c6 r9c7 r8c6
+            renban: r6c7 r5c6 r4c5
+            whisper: r6c3 r7c3 r7c4
+            ",
+        )?;
+        assert_eq!(lines.len(), 15);
+        let puzzle = Sudoku::from_str(
+            "...7.....4.7.1.................................................8.....7...........",
+        )?;
+        let mut pipeline = Pipeline::default();
+        pipeline.extend(lines);
+
+        // with `all_solutions` this also proves that the solution is unique
+        assert_eq!(
+            solver::solve_with(puzzle.clone(), &pipeline)?.0.to_string(),
+            "693784512487512936125963874932651487568247391741398625319475268856129743274836159"
+        );
+        if cfg!(feature = "all_solutions") {
+            assert!(matches!(
+                solver::solve(puzzle),
+                Err(Error::MultipleSolutions)
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_classic_board() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        let lines = parse_lines(&geometry, "thermo: r1c2 r1c1")?;
+        let mut sudoku = Sudoku::default();
+
+        lines[0].propagate(&mut sudoku)?;
+        assert_eq!(sudoku.bitboard[0], 0b11_1111_1100);
+        assert_eq!(sudoku.bitboard[1], 0b1_1111_1110);
+
+        // the solver checks the lines once every digit is placed
+        let solution = Sudoku::from_str(
+            "693784512487512936125963874932651487568247391741398625319475268856129743274836159",
+        )?;
+        let mut pipeline = Pipeline::default();
+        pipeline.extend(lines);
+        assert!(matches!(
+            solver::solve_with(solution, &pipeline),
+            Err(Error::NoSolution)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_errors() -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        assert!(Thermometer::new(&geometry, vec![]).is_err());
+        assert!(Whisper::new(&geometry, vec![0]).is_err());
+        assert!(Palindrome::new(&geometry, vec![0, 81]).is_err());
+        assert!(Arrow::new(&geometry, 0, vec![]).is_err());
+        assert!(Arrow::new(&geometry, 0, vec![1, 0]).is_err());
+        assert!(Renban::new(&geometry, (0..10).collect()).is_err());
+        assert!(Renban::new(&Geometry::square(2)?, vec![0, 1, 2, 3, 4]).is_err());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("thermo r1c1 r1c2")]
+    #[case("snake: r1c1 r1c2")]
+    #[case("thermo: r1c1")]
+    #[case("renban: r1c1 r1c1")]
+    #[case("renban: r1c1 r1c2 r1c3 r1c4 r1c5 r1c6 r1c7 r1c8 r1c9 r2c1")]
+    fn test_parse_errors(#[case] line: &str) -> Result<()> {
+        let geometry = Geometry::square(3)?;
+        assert!(parse_lines::<Sudoku<Geometry>>(&geometry, line).is_err());
+        Ok(())
+    }
+}
@@ -29,11 +29,14 @@
 )]
 
 use derive_more as _;
+#[cfg(test)]
 use rstest as _;
 
 #[allow(unused)]
 use rayon::prelude::*;
-use rust_sudoku_solver::{solver, Error, Result, Sudoku};
+use rust_sudoku_solver::{
+    solver, Error, Heuristic, Pipeline, Result, SolveStats, Sudoku, TechniqueStats,
+};
 use std::{fmt::Write, fs::read_to_string, str::FromStr, time::Instant};
 
 fn read_lines(filename: &str) -> Result<Vec<Sudoku>> {
@@ -45,9 +48,11 @@ fn read_lines(filename: &str) -> Result<Vec<Sudoku>> {
         .collect()
 }
 
-fn solve_all_in_file(filename: &str) -> Result<(Vec<Sudoku>, Vec<Sudoku>)> {
+type Solution = (Sudoku, SolveStats);
+
+fn solve_all_in_file(filename: &str) -> Result<(Vec<Sudoku>, Vec<Solution>)> {
     let sudokus = read_lines(filename)?;
-    let solutions: Result<Vec<Sudoku>> = sudokus
+    let solutions: Result<Vec<Solution>> = sudokus
         .iter()
         .cloned()
         // .into_par_iter()
@@ -58,11 +63,11 @@ fn solve_all_in_file(filename: &str) -> Result<(Vec<Sudoku>, Vec<Sudoku>)> {
 
 #[allow(unused)]
 #[allow(clippy::print_stdout)]
-fn print_hardest_sudoku(sudokus: &[Sudoku], solutions: &[Sudoku]) -> Result<()> {
+fn print_hardest_sudoku(sudokus: &[Sudoku], solutions: &[Solution]) -> Result<()> {
     let (hardest_sudoku, _) = sudokus
         .iter()
         .zip(solutions.iter())
-        .max_by_key(|(_, solution)| solution.num_recursions)
+        .max_by_key(|(_, (_, stats))| stats.num_recursions)
         .ok_or(Error::SolveError)?;
 
     println!("hardest sudoku: {hardest_sudoku}");
@@ -81,19 +86,33 @@ fn benchmark_file(filename: &str) -> Result<()> {
 
     // print_hardest_sudoku(&sudokus, &solutions)?;
 
-    let total_recursions: i32 = solutions.iter().map(|s| s.num_recursions).sum();
-    let max_recursions: i32 = solutions
+    let max_recursions: u64 = solutions
         .iter()
-        .map(|s| s.num_recursions)
+        .map(|(_, stats)| stats.num_recursions)
         .max()
         .ok_or(Error::SolveError)?;
-    let recursions_per_puzzle = f64::from(total_recursions) / n as f64;
-
-    let total_guesses: i32 = solutions.iter().map(|s| s.guesses).sum();
-    let avg_guesses = f64::from(total_guesses) / n as f64;
+    let mut stats = SolveStats::default();
+    for (_, solution_stats) in &solutions {
+        stats += *solution_stats;
+    }
+    let recursions_per_puzzle = stats.num_recursions as f64 / n as f64;
+    let avg_guesses = stats.guesses as f64 / n as f64;
+    let per_puzzle = |technique: TechniqueStats| {
+        (technique.placements + technique.eliminations) as f64 / n as f64
+    };
 
     print!("\r{filename:45}{n:>12}    {elapsed:10.2?}{time_per_puzzle:12.2?}");
-    println!("{recursions_per_puzzle:15.4}{max_recursions:15}{avg_guesses:12.4}");
+    print!("{recursions_per_puzzle:15.4}{max_recursions:15}{avg_guesses:12.4}");
+    print!("{:10.2}", per_puzzle(stats.visible_singles));
+    print!("{:10.2}", per_puzzle(stats.hidden_singles));
+    print!("{:10.2}", per_puzzle(stats.pointing));
+    print!("{:10.2}", per_puzzle(stats.doubles));
+    print!("{:10.2}", per_puzzle(stats.propagation));
+    println!(
+        "{:10.2}{:10}",
+        stats.branches_pruned as f64 / n as f64,
+        stats.max_depth
+    );
     Ok(())
 }
 
@@ -117,7 +136,7 @@ fn output_solutions(filename: &str) -> Result<String> {
 }
 
 fn write_row(ret: &mut String, sudoku: &Sudoku) -> Result<()> {
-    let solution = solver::solve(sudoku.clone())?;
+    let (solution, _) = solver::solve(sudoku.clone())?;
     if !is_valid(sudoku, &solution) {
         return Err(Error::SolveError);
     }
@@ -129,14 +148,21 @@ fn write_row(ret: &mut String, sudoku: &Sudoku) -> Result<()> {
 #[allow(unused)]
 fn benchmark() {
     println!(
-        "{:45}{:>12}    {:10}{:12}{:>15}{:>15}{:>12}",
+        "{:45}{:>12}    {:10}{:12}{:>15}{:>15}{:>12}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}",
         "filename",
         "num sudokus",
         "time",
         "per puzzle",
         "avg_recursions",
         "max_recursions",
-        "avg_guesses"
+        "avg_guesses",
+        "singles",
+        "hidden",
+        "pointing",
+        "doubles",
+        "propagate",
+        "pruned",
+        "max_depth"
     );
     benchmark_file("data-sets/easiest.txt");
     benchmark_file("data-sets/hard_sudokus.txt");
@@ -147,6 +173,54 @@ fn benchmark() {
     // benchmark_file("data-sets/ph1910_02.txt");
 }
 
+const HEURISTICS: [Heuristic; 5] = [
+    Heuristic::MinimumRemainingValues,
+    Heuristic::MrvDegree,
+    Heuristic::Bilocal,
+    Heuristic::LeastConstrainingValue,
+    Heuristic::Random(0),
+];
+
+#[allow(clippy::print_stdout)]
+fn compare_heuristics_in_file(filename: &str) -> Result<()> {
+    let sudokus = read_lines(filename)?;
+    let pipeline = Pipeline::default();
+    print!("{filename:45}");
+    for heuristic in HEURISTICS {
+        let mut stats = SolveStats::default();
+        for sudoku in &sudokus {
+            let (_, solution_stats) =
+                solver::solve_with_heuristic(sudoku.clone(), &pipeline, heuristic)?;
+            stats += solution_stats;
+        }
+        print!("{:>10}/{:<9}", stats.num_recursions, stats.guesses);
+    }
+    println!();
+    Ok(())
+}
+
+/// Prints the total recursions and guesses each heuristic needs for the given
+/// files, or for the benchmark data sets if none are given.
+#[allow(clippy::print_stdout)]
+fn compare_heuristics(filenames: &[String]) -> Result<()> {
+    print!("{:45}", "recursions/guesses");
+    for heuristic in HEURISTICS {
+        print!("{:^20}", heuristic.to_string());
+    }
+    println!();
+    if filenames.is_empty() {
+        compare_heuristics_in_file("data-sets/easiest.txt")?;
+        compare_heuristics_in_file("data-sets/hard_sudokus.txt")?;
+        compare_heuristics_in_file("data-sets/all_17_clue_sudokus.txt")?;
+        compare_heuristics_in_file("data-sets/puzzles6_forum_hardest_1106.txt")?;
+        compare_heuristics_in_file("data-sets/ph1307.txt")
+    } else {
+        filenames
+            .iter()
+            .try_for_each(|filename| compare_heuristics_in_file(filename))
+    }
+}
+
 #[allow(clippy::print_stdout)]
 fn parse_and_expect(filename: &str, expected_digest: &str) -> Result<()> {
     let output = output_solutions(filename).unwrap_or_default();
@@ -162,10 +236,10 @@ fn parse_and_expect(filename: &str, expected_digest: &str) -> Result<()> {
 
 struct SolutionData {
     filename: String,
-    expected_recursions: i32,
-    recursions: i32,
-    expected_guesses: i32,
-    guesses: i32,
+    expected_recursions: u64,
+    recursions: u64,
+    expected_guesses: u64,
+    guesses: u64,
 }
 
 impl SolutionData {
@@ -204,10 +278,10 @@ fn check_solution_data(solution_data: &SolutionData) -> Result<()> {
     }
 }
 
-fn count_recursions(filename: &str, expected_recursions: i32, expected_guesses: i32) -> Result<()> {
+fn count_recursions(filename: &str, expected_recursions: u64, expected_guesses: u64) -> Result<()> {
     let (_, solutions) = solve_all_in_file(filename).unwrap_or_default();
-    let recursions = solutions.iter().map(|s| s.num_recursions).sum::<i32>();
-    let guesses = solutions.iter().map(|s| s.guesses).sum::<i32>();
+    let recursions = solutions.iter().map(|(_, s)| s.num_recursions).sum::<u64>();
+    let guesses = solutions.iter().map(|(_, s)| s.guesses).sum::<u64>();
 
     let solution_data = SolutionData {
         filename: filename.to_string(),
@@ -239,6 +313,12 @@ fn validate_recursions() -> Result<()> {
 }
 
 fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some((flag, filenames)) = args.split_first() {
+        if flag == "--compare-heuristics" {
+            return compare_heuristics(filenames);
+        }
+    }
     benchmark();
     validate_hashes()?;
     validate_recursions()?;
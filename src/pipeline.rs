@@ -0,0 +1,175 @@
+use core::fmt;
+
+use crate::{
+    board::Board,
+    stats::SolveStats,
+    sudoku::Sudoku,
+    technique::{
+        apply_counting, HiddenSingles, HiddenZeroes, Progress, Technique, Triples, VisibleDoubles,
+        VisibleSingles,
+    },
+    Result,
+};
+
+/// An ordered list of techniques for a kind of [`Board`], run once each per
+/// pass.
+///
+/// The default pipeline runs the same checks in the same order as
+/// [`check_constraints`](crate::check_constraints). Building it does not
+/// allocate, only techniques added with [`Pipeline::with`] are boxed.
+pub struct Pipeline<B: 'static = Sudoku> {
+    builtin: &'static [&'static dyn Technique<B>],
+    #[cfg(feature = "std")]
+    techniques: Vec<Box<dyn Technique<B>>>,
+}
+
+impl<B: Board> Default for Pipeline<B> {
+    fn default() -> Self {
+        Self::from_static(Self::DEFAULT_TECHNIQUES)
+    }
+}
+
+/// Appends techniques to the end of the pipeline, such as the constraints of
+/// a variant.
+#[cfg(feature = "std")]
+impl<B: Board, T: Technique<B> + 'static> Extend<T> for Pipeline<B> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, techniques: I) {
+        for technique in techniques {
+            self.push(technique);
+        }
+    }
+}
+
+impl<B: Board> fmt::Debug for Pipeline<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.techniques().map(Technique::name))
+            .finish()
+    }
+}
+
+impl<B: Board> Pipeline<B> {
+    /// The techniques of the default pipeline.
+    const DEFAULT_TECHNIQUES: &'static [&'static dyn Technique<B>] = &[
+        &Triples,
+        &VisibleSingles,
+        &HiddenZeroes,
+        &HiddenSingles,
+        &VisibleDoubles,
+    ];
+
+    /// A pipeline without any techniques.
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self::from_static(&[])
+    }
+
+    /// A pipeline running the given techniques, which needs neither `std` nor
+    /// an allocator.
+    #[must_use]
+    pub const fn from_static(techniques: &'static [&'static dyn Technique<B>]) -> Self {
+        Self {
+            builtin: techniques,
+            #[cfg(feature = "std")]
+            techniques: Vec::new(),
+        }
+    }
+
+    /// Append a technique to the end of the pipeline.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn with(mut self, technique: impl Technique<B> + 'static) -> Self {
+        self.push(technique);
+        self
+    }
+
+    /// Append a technique to the end of the pipeline.
+    #[cfg(feature = "std")]
+    pub fn push(&mut self, technique: impl Technique<B> + 'static) {
+        self.techniques.push(Box::new(technique));
+    }
+
+    pub fn techniques(&self) -> impl Iterator<Item = &dyn Technique<B>> {
+        let builtin = self.builtin.iter().copied();
+        #[cfg(feature = "std")]
+        let builtin = builtin.chain(self.techniques.iter().map(AsRef::as_ref));
+        builtin
+    }
+
+    /// Run every technique once, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board is invalid.
+    #[inline]
+    pub fn run(&self, board: &mut B) -> Result<Progress> {
+        let mut progress = Progress::Unchanged;
+        for technique in self.techniques() {
+            if technique.apply(board)? == Progress::Changed {
+                progress = Progress::Changed;
+            }
+        }
+        Ok(progress)
+    }
+
+    /// Run every technique once, in order, and count what each of them did.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board is invalid.
+    #[inline]
+    pub fn run_counting(&self, board: &mut B, stats: &mut SolveStats) -> Result<Progress> {
+        let mut progress = Progress::Unchanged;
+        for technique in self.techniques() {
+            if apply_counting(technique, board, stats)? == Progress::Changed {
+                progress = Progress::Changed;
+            }
+        }
+        Ok(progress)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[rstest]
+    #[case("...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..")]
+    #[case("000000036030000050200000000000060800700000400000053000000700210060900000001000000")]
+    fn test_default_matches_check_constraints(#[case] input: &str) -> Result<()> {
+        let mut expected = Sudoku::from_str(input)?;
+        let mut sudoku = expected.clone();
+        check_constraints(&mut expected)?;
+        Pipeline::default().run(&mut sudoku)?;
+
+        assert_eq!(sudoku.bitboard, expected.bitboard);
+        assert_eq!(sudoku.digits, expected.digits);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_static() {
+        const TECHNIQUES: &[&dyn Technique] = &[&HiddenSingles, &VisibleSingles];
+        let pipeline = Pipeline::from_static(TECHNIQUES);
+        assert_eq!(
+            format!("{pipeline:?}"),
+            r#"["Hidden Singles", "Visible Singles"]"#
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_debug() {
+        let pipeline = Pipeline::<Sudoku>::empty()
+            .with(HiddenSingles)
+            .with(VisibleSingles);
+        assert_eq!(
+            format!("{pipeline:?}"),
+            r#"["Hidden Singles", "Visible Singles"]"#
+        );
+    }
+}
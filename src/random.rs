@@ -0,0 +1,61 @@
+/// A small, seedable `SplitMix64` generator. Good enough for shuffling search
+/// order, and keeps the crate free of extra dependencies.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`. The modulo bias is negligible for the small ranges
+    /// used by the solver.
+    pub const fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reproducible() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+        let first = a.next_u64();
+
+        assert_eq!(first, b.next_u64());
+        assert_ne!(first, c.next_u64());
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut rng = Rng::new(7);
+        let mut items = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        rng.shuffle(&mut items);
+
+        assert_ne!(items, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        items.sort_unstable();
+        assert_eq!(items, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+}
@@ -0,0 +1,297 @@
+use core::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::consts::BitWidth;
+
+/// The largest supported width, so that every digit fits in a candidate mask.
+pub const MAX_WIDTH: usize = 25;
+
+/// The number of cells in the largest supported grid.
+pub const MAX_SIZE: usize = MAX_WIDTH * MAX_WIDTH;
+
+/// The layout of a [`Sudoku`](crate::Sudoku): its width, and the units whose
+/// cells must all hold different digits.
+///
+/// Units are numbered rows first, then columns, then boxes, and then any extra
+/// units, so `units_of` lists the row, column and box of a cell first.
+pub trait Shape: Clone + fmt::Debug + PartialEq + Eq + 'static {
+    /// One value per cell, such as the candidates or the digits of a grid.
+    type Cells: Clone
+        + fmt::Debug
+        + PartialEq
+        + Eq
+        + AsRef<[BitWidth]>
+        + AsMut<[BitWidth]>
+        + Index<usize, Output = BitWidth>
+        + IndexMut<usize>;
+
+    /// The number of digits, and of cells in every row and column.
+    fn width(&self) -> usize;
+
+    /// The number of cells.
+    #[inline]
+    fn size(&self) -> usize {
+        self.width() * self.width()
+    }
+
+    /// Every digit of the grid as a candidate mask.
+    #[inline]
+    fn mask(&self) -> BitWidth {
+        ((1 << (self.width() + 1)) - 1) & !1
+    }
+
+    /// The same value in every cell.
+    fn cells(&self, value: BitWidth) -> Self::Cells;
+
+    fn num_units(&self) -> usize;
+
+    /// The cells of a unit, in ascending order.
+    fn unit(&self, unit: usize) -> &[usize];
+
+    /// The units that a cell belongs to.
+    fn units_of(&self, idx: usize) -> &[usize];
+
+    /// The other cells of the row (`kind` 0), column (1) or box (2) of a
+    /// cell, in ascending order.
+    fn peers(&self, idx: usize, kind: usize) -> &[usize];
+
+    /// The cells that can't hold the same digit as a cell, in ascending order.
+    fn neighbors(&self, idx: usize) -> &[usize];
+}
+
+/// A grid of boxes that are `HEIGHT` rows tall and `WIDTH` columns wide, such
+/// as the classic 3x3 boxes or the 2x3 boxes of a 6x6 grid. Its tables are
+/// built at compile time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Boxes<const HEIGHT: usize, const WIDTH: usize>;
+
+/// The classic 9x9 Sudoku.
+pub type Classic = Boxes<3, 3>;
+
+/// Every unit as a list of cells: rows, then columns, then boxes.
+const fn unit_table<const N: usize, const UNITS: usize>(
+    height: usize,
+    width: usize,
+) -> [[usize; N]; UNITS] {
+    let mut units = [[0; N]; UNITS];
+    let mut unit = 0;
+    while unit < N {
+        let (top, left) = (unit / height * height, unit % height * width);
+        let mut i = 0;
+        while i < N {
+            units[unit][i] = unit * N + i;
+            units[N + unit][i] = i * N + unit;
+            units[2 * N + unit][i] = (top + i / width) * N + left + i % width;
+            i += 1;
+        }
+        unit += 1;
+    }
+    units
+}
+
+/// The row, column and box of every cell.
+const fn units_of_table<const N: usize, const SIZE: usize>(
+    height: usize,
+    width: usize,
+) -> [[usize; 3]; SIZE] {
+    let mut units_of = [[0; 3]; SIZE];
+    let mut idx = 0;
+    while idx < SIZE {
+        let (row, col) = (idx / N, idx % N);
+        units_of[idx] = [row, N + col, 2 * N + row / height * height + col / width];
+        idx += 1;
+    }
+    units_of
+}
+
+/// The cells that share a row, column or box with every cell, row by row.
+const fn neighbor_table<const N: usize, const SIZE: usize, const NEIGHBORS: usize>(
+    height: usize,
+    width: usize,
+) -> [[usize; NEIGHBORS]; SIZE] {
+    let mut neighbors = [[0; NEIGHBORS]; SIZE];
+    let mut idx = 0;
+    while idx < SIZE {
+        let (row, col) = (idx / N, idx % N);
+        let left = col / width * width;
+        let mut count = 0;
+        let mut other_row = 0;
+        while other_row < N {
+            // the whole row, the box within the band, or just the column
+            let (start, end) = if other_row == row {
+                (0, N)
+            } else if other_row / height == row / height {
+                (left, left + width)
+            } else {
+                (col, col + 1)
+            };
+            let mut other_col = start;
+            while other_col < end {
+                if other_row != row || other_col != col {
+                    neighbors[idx][count] = other_row * N + other_col;
+                    count += 1;
+                }
+                other_col += 1;
+            }
+            other_row += 1;
+        }
+        idx += 1;
+    }
+    neighbors
+}
+
+/// The other cells of the row, column and box of every cell.
+const fn peer_table<const N: usize, const UNITS: usize, const SIZE: usize, const PEERS: usize>(
+    height: usize,
+    width: usize,
+) -> [[[usize; PEERS]; 3]; SIZE] {
+    let units = unit_table::<N, UNITS>(height, width);
+    let units_of = units_of_table::<N, SIZE>(height, width);
+    let mut peers = [[[0; PEERS]; 3]; SIZE];
+    let mut idx = 0;
+    while idx < SIZE {
+        let mut kind = 0;
+        while kind < 3 {
+            let unit = &units[units_of[idx][kind]];
+            let (mut i, mut count) = (0, 0);
+            while i < N {
+                if unit[i] != idx {
+                    peers[idx][kind][count] = unit[i];
+                    count += 1;
+                }
+                i += 1;
+            }
+            kind += 1;
+        }
+        idx += 1;
+    }
+    peers
+}
+
+macro_rules! boxes {
+    ($($height:literal x $width:literal),* $(,)?) => {$(
+        impl Shape for Boxes<$height, $width> {
+            type Cells = [BitWidth; $height * $width * $height * $width];
+
+            #[inline]
+            fn width(&self) -> usize {
+                $height * $width
+            }
+
+            #[inline]
+            fn cells(&self, value: BitWidth) -> Self::Cells {
+                [value; $height * $width * $height * $width]
+            }
+
+            #[inline]
+            fn num_units(&self) -> usize {
+                3 * $height * $width
+            }
+
+            #[inline]
+            fn unit(&self, unit: usize) -> &[usize] {
+                static UNITS: [[usize; $height * $width]; 3 * $height * $width] =
+                    unit_table($height, $width);
+                &UNITS[unit]
+            }
+
+            #[inline]
+            fn units_of(&self, idx: usize) -> &[usize] {
+                static UNITS_OF: [[usize; 3]; $height * $width * $height * $width] =
+                    units_of_table::<{ $height * $width }, _>($height, $width);
+                &UNITS_OF[idx]
+            }
+
+            #[inline]
+            fn peers(&self, idx: usize, kind: usize) -> &[usize] {
+                static PEERS: [
+                    [[usize; $height * $width - 1]; 3];
+                    $height * $width * $height * $width
+                ] = peer_table::<{ $height * $width }, { 3 * $height * $width }, _, _>(
+                    $height, $width,
+                );
+                &PEERS[idx][kind]
+            }
+
+            #[inline]
+            fn neighbors(&self, idx: usize) -> &[usize] {
+                static NEIGHBORS: [
+                    [usize; 3 * $height * $width - $height - $width - 1];
+                    $height * $width * $height * $width
+                ] = neighbor_table::<{ $height * $width }, _, _>($height, $width);
+                &NEIGHBORS[idx]
+            }
+        }
+    )*};
+}
+
+boxes!(2 x 2, 2 x 3, 3 x 2, 2 x 4, 4 x 2, 3 x 3, 3 x 4, 4 x 3, 4 x 4, 5 x 5);
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::consts;
+    use rstest::rstest;
+
+    #[test]
+    fn test_matches_consts() {
+        let shape = Classic::default();
+
+        assert_eq!(shape.size(), consts::SIZE);
+        assert_eq!(shape.mask(), consts::MASK);
+        for idx in 0..consts::SIZE {
+            assert_eq!(shape.neighbors(idx), consts::NEIGHBORS[idx]);
+            assert_eq!(shape.peers(idx, 2), consts::SAME_CELL[idx]);
+            assert_eq!(shape.units_of(idx)[2], 18 + consts::CELL_LOOKUP[idx]);
+        }
+        for unit in 0..consts::WIDTH {
+            assert_eq!(shape.unit(unit), consts::ROWS[unit]);
+            assert_eq!(shape.unit(consts::WIDTH + unit), consts::COLS[unit]);
+            assert_eq!(shape.unit(2 * consts::WIDTH + unit), consts::CELLS[unit]);
+        }
+    }
+
+    #[rstest]
+    #[case(Boxes::<2, 3>, 6, 12)]
+    #[case(Boxes::<4, 4>, 16, 39)]
+    #[case(Boxes::<5, 5>, 25, 64)]
+    fn test_tables(#[case] shape: impl Shape, #[case] width: usize, #[case] num_neighbors: usize) {
+        assert_eq!(shape.width(), width);
+        for unit in 0..shape.num_units() {
+            for &idx in shape.unit(unit) {
+                assert!(shape.units_of(idx).contains(&unit));
+            }
+        }
+        for idx in 0..shape.size() {
+            for kind in 0..3 {
+                let unit = shape.unit(shape.units_of(idx)[kind]);
+                let others: Vec<_> = unit.iter().copied().filter(|&other| other != idx).collect();
+                assert_eq!(shape.peers(idx, kind), others);
+            }
+            let neighbors = shape.neighbors(idx);
+            assert_eq!(neighbors.len(), num_neighbors);
+            assert!(neighbors.windows(2).all(|pair| pair[0] < pair[1]));
+            for &other in neighbors {
+                let shared = shape.units_of(other);
+                assert!(shape.units_of(idx).iter().any(|unit| shared.contains(unit)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rectangular_boxes() {
+        let shape = Boxes::<2, 3>;
+
+        assert_eq!(shape.unit(12), [0, 1, 2, 6, 7, 8]);
+        assert_eq!(shape.unit(13), [3, 4, 5, 9, 10, 11]);
+        assert_eq!(shape.unit(14), [12, 13, 14, 18, 19, 20]);
+        assert_eq!(shape.unit(17), [27, 28, 29, 33, 34, 35]);
+        assert_eq!(
+            shape.neighbors(7),
+            [0, 1, 2, 6, 8, 9, 10, 11, 13, 19, 25, 31]
+        );
+    }
+}
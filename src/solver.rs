@@ -1,36 +1,120 @@
 use crate::{
+    board::Board,
     consts,
-    hidden::{check_all_hidden_zeroes, place_all_hidden_singles},
-    sudoku::Sudoku,
+    heuristic::{self, Branch, Heuristic},
+    hidden::check_all_hidden_zeroes,
+    pipeline::Pipeline,
+    random::Rng,
+    shape::Shape,
+    stats::{SolveStats, TechniqueStats},
+    technique::{self, HiddenSingles, Progress, Triples, VisibleDoubles, VisibleSingles},
     triples::check_triples,
     visible::{check_all_visible_doubles, place_all_visible_singles},
     Error, Result,
 };
 
-/// Solves a Sudoku puzzle. Returns the solved Sudoku if successful.
-/// If multiple solutions are possible, only one is returned.
+/// Solves a Sudoku puzzle. Returns the solved Sudoku if successful, along with
+/// statistics about the search. If multiple solutions are possible, only one
+/// is returned.
 ///
 /// # Errors
 ///
 /// Returns an error if the Sudoku is invalid.
-pub fn solve(mut sudoku: Sudoku) -> Result<Sudoku> {
+pub fn solve<B: Board>(mut sudoku: B) -> Result<(B, SolveStats)> {
+    let pipeline = Pipeline::default();
+    let mut context = Context::new(&pipeline, Heuristic::default());
     // heuristic for attempting to solve the puzzle
-    place_all_visible_singles(&mut sudoku)?;
+    context.apply(&VisibleSingles, &mut sudoku)?;
     if sudoku.is_solved() {
-        return Ok(sudoku);
+        return Ok((sudoku, context.stats));
     }
-    place_all_hidden_singles(&mut sudoku)?;
+    context.apply(&HiddenSingles, &mut sudoku)?;
     if sudoku.is_solved() {
-        return Ok(sudoku);
+        return Ok((sudoku, context.stats));
     }
-    check_triples(&mut sudoku)?;
-    check_all_visible_doubles(&mut sudoku)?;
-    place_all_hidden_singles(&mut sudoku)?;
-    place_all_visible_singles(&mut sudoku)?;
+    context.apply(&Triples, &mut sudoku)?;
+    context.apply(&VisibleDoubles, &mut sudoku)?;
+    context.apply(&HiddenSingles, &mut sudoku)?;
+    context.apply(&VisibleSingles, &mut sudoku)?;
     if sudoku.is_solved() {
-        return Ok(sudoku);
+        return Ok((sudoku, context.stats));
+    }
+    let solution = context.solve_recursive(&mut sudoku, 0)?;
+    Ok((solution, context.stats))
+}
+
+/// Solves a Sudoku puzzle, running `pipeline` to propagate constraints before
+/// every guess. If multiple solutions are possible, only one is returned.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn solve_with<B: Board>(sudoku: B, pipeline: &Pipeline<B>) -> Result<(B, SolveStats)> {
+    solve_with_heuristic(sudoku, pipeline, Heuristic::default())
+}
+
+/// Solves a Sudoku puzzle like [`solve_with`], using `heuristic` to decide
+/// what to guess whenever the pipeline gets stuck.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn solve_with_heuristic<B: Board>(
+    mut sudoku: B,
+    pipeline: &Pipeline<B>,
+    heuristic: Heuristic,
+) -> Result<(B, SolveStats)> {
+    let mut context = Context::new(pipeline, heuristic);
+    let solution = context.solve_recursive(&mut sudoku, 0)?;
+    Ok((solution, context.stats))
+}
+
+/// Finds a random solution of the Sudoku.
+///
+/// Cells and digits are guessed in an order shuffled by `seed`. The same seed
+/// always gives the same grid, so starting from `Sudoku::default()` gives a
+/// reproducible random full grid. Unlike [`solve`], this never checks whether
+/// the solution is unique.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku has no solution.
+pub fn solve_random<B: Board>(mut sudoku: B, seed: u64) -> Result<(B, SolveStats)> {
+    let pipeline = Pipeline::default();
+    #[allow(unused_mut)]
+    let mut context = Context::new(&pipeline, Heuristic::Random(seed));
+    #[cfg(feature = "all_solutions")]
+    {
+        context.first_only = true;
+    }
+    let solution = context.solve_recursive(&mut sudoku, 0)?;
+    Ok((solution, context.stats))
+}
+
+/// The outcome of solving a Sudoku without guessing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Solved,
+    /// None of the techniques can make any more progress.
+    Stuck,
+    /// A technique found that the Sudoku has no solution.
+    Contradiction,
+}
+
+/// Runs `techniques` until the Sudoku is solved or none of them make any
+/// progress, without ever guessing. Returns the Sudoku as far as it got.
+#[must_use]
+pub fn solve_logically<B: Board>(mut sudoku: B, techniques: &Pipeline<B>) -> (B, Status) {
+    loop {
+        if sudoku.is_solved() {
+            return (sudoku, Status::Solved);
+        }
+        match techniques.run(&mut sudoku) {
+            Ok(Progress::Changed) => {}
+            Ok(Progress::Unchanged) => return (sudoku, Status::Stuck),
+            Err(_) => return (sudoku, Status::Contradiction),
+        }
     }
-    solve_recursive(&mut sudoku)
 }
 
 /// Perform all constraint checks on the Sudoku.
@@ -39,28 +123,16 @@ pub fn solve(mut sudoku: Sudoku) -> Result<Sudoku> {
 ///
 /// Returns an error if the Sudoku is invalid.
 #[inline]
-pub fn check_constraints(sudoku: &mut Sudoku) -> Result<()> {
+pub fn check_constraints<B: Board>(sudoku: &mut B) -> Result<()> {
     check_triples(sudoku)?;
     place_all_visible_singles(sudoku)?;
     check_all_hidden_zeroes(sudoku)?;
-    place_all_hidden_singles(sudoku)?;
+    sudoku.place_hidden_singles()?;
     check_all_visible_doubles(sudoku)
 }
 
-fn solve_recursive(sudoku: &mut Sudoku) -> Result<Sudoku> {
-    sudoku.num_recursions += 1;
-    check_constraints(sudoku)?;
-    if sudoku.is_solved() {
-        Ok(sudoku.clone())
-    } else if let Some(idx) = get_next_idx(sudoku) {
-        branch_possibilities(sudoku, idx)
-    } else {
-        Err(Error::from(sudoku))
-    }
-}
-
-pub(crate) fn place_and_propagate(
-    sudoku: &mut Sudoku,
+pub(crate) fn place_and_propagate<B: Board>(
+    sudoku: &mut B,
     idx: usize,
     digit: consts::BitWidth,
 ) -> Result<()> {
@@ -68,82 +140,136 @@ pub(crate) fn place_and_propagate(
     unit_propagate(sudoku, idx)
 }
 
-#[cfg(not(feature = "all_solutions"))]
-fn branch_possibilities(sudoku: &mut Sudoku, idx: usize) -> Result<Sudoku> {
-    let bitboard = sudoku.bitboard[idx];
-    sudoku.guesses += i32::from(bitboard.count_ones() > 1);
-    let start = bitboard.trailing_zeros() as consts::BitWidth;
-    let end = consts::NUM_BITS - bitboard.leading_zeros() as consts::BitWidth;
-
-    (start..end)
-        .filter(|&n| bitboard & (1 << n) > 0)
-        .map(|digit| check_branch(sudoku, idx, digit))
-        .find_map(Result::ok)
-        .ok_or_else(|| Error::from(sudoku))
+/// The state shared by every branch of a single search.
+struct Context<'a, B: Board> {
+    pipeline: &'a Pipeline<B>,
+    heuristic: Heuristic,
+    rng: Rng,
+    stats: SolveStats,
+    /// Stop at the first solution instead of looking for a second one.
+    #[cfg(feature = "all_solutions")]
+    first_only: bool,
 }
 
-#[cfg(feature = "all_solutions")]
-fn branch_possibilities(sudoku: &mut Sudoku, idx: usize) -> Result<Sudoku> {
-    let bitboard = sudoku.bitboard[idx];
-    sudoku.guesses += i32::from(bitboard.count_ones() > 1);
-    let start = bitboard.trailing_zeros() as consts::BitWidth;
-    let end = consts::NUM_BITS - bitboard.leading_zeros() as consts::BitWidth;
+impl<'a, B: Board> Context<'a, B> {
+    fn new(pipeline: &'a Pipeline<B>, heuristic: Heuristic) -> Self {
+        let seed = match heuristic {
+            Heuristic::Random(seed) => seed,
+            _ => 0,
+        };
+        Self {
+            pipeline,
+            heuristic,
+            rng: Rng::new(seed),
+            stats: SolveStats::default(),
+            #[cfg(feature = "all_solutions")]
+            first_only: false,
+        }
+    }
 
-    let mut solution = None;
+    #[inline]
+    fn apply(
+        &mut self,
+        technique: &dyn technique::Technique<B>,
+        sudoku: &mut B,
+    ) -> Result<Progress> {
+        technique::apply_counting(technique, sudoku, &mut self.stats)
+    }
 
-    for n in start..end {
-        if bitboard & (1 << n) == 0 {
-            continue;
+    fn solve_recursive(&mut self, sudoku: &mut B, depth: u32) -> Result<B> {
+        self.stats.num_recursions += 1;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        self.pipeline.run_counting(sudoku, &mut self.stats)?;
+        if sudoku.is_solved() {
+            Ok(sudoku.clone())
+        } else if let Some(branch) = heuristic::next_branch(sudoku, self.heuristic, &mut self.rng) {
+            self.branch_possibilities(sudoku, &branch, depth)
+        } else {
+            Err(Error::NoSolution)
         }
-        let res = check_branch(sudoku, idx, n);
-        match res {
-            Ok(sol) => match solution {
-                Some(_) => return Err(Error::MultipleSolutions),
-                None => solution = Some(sol),
-            },
-            Err(Error::MultipleSolutions) => return Err(Error::MultipleSolutions),
-            _ => {}
+    }
+
+    #[cfg(not(feature = "all_solutions"))]
+    fn branch_possibilities(&mut self, sudoku: &B, branch: &Branch, depth: u32) -> Result<B> {
+        self.stats.guesses += u64::from(branch.as_slice().len() > 1);
+        branch
+            .as_slice()
+            .iter()
+            .map(|&(idx, digit)| self.check_branch(sudoku, idx, digit, depth))
+            .find_map(Result::ok)
+            .ok_or(Error::NoSolution)
+    }
+
+    #[cfg(feature = "all_solutions")]
+    fn branch_possibilities(&mut self, sudoku: &B, branch: &Branch, depth: u32) -> Result<B> {
+        self.stats.guesses += u64::from(branch.as_slice().len() > 1);
+        let mut solution = None;
+
+        for &(idx, digit) in branch.as_slice() {
+            let res = self.check_branch(sudoku, idx, digit, depth);
+            match res {
+                Ok(sol) if self.first_only => return Ok(sol),
+                Ok(sol) => match solution {
+                    Some(_) => return Err(Error::MultipleSolutions),
+                    None => solution = Some(sol),
+                },
+                Err(Error::MultipleSolutions) => return Err(Error::MultipleSolutions),
+                _ => {}
+            }
         }
+        solution.ok_or(Error::NoSolution)
     }
-    solution.map_or_else(|| Err(Error::from(sudoku)), Ok)
-}
 
-#[inline]
-fn check_branch(sudoku: &mut Sudoku, idx: usize, digit: consts::BitWidth) -> Result<Sudoku> {
-    let mut cloned_board = sudoku.clone();
-    place_and_propagate(&mut cloned_board, idx, digit)?;
-    solve_recursive(&mut cloned_board).map_err(|error| sudoku.store_stats(error))
+    #[inline]
+    fn check_branch(
+        &mut self,
+        sudoku: &B,
+        idx: usize,
+        digit: consts::BitWidth,
+        depth: u32,
+    ) -> Result<B> {
+        let mut cloned_board = sudoku.clone();
+        place_and_propagate(&mut cloned_board, idx, digit)
+            .and_then(|()| {
+                self.stats.propagation +=
+                    TechniqueStats::since(&cloned_board, sudoku.num_digits(), sudoku.num_removed());
+                self.solve_recursive(&mut cloned_board, depth + 1)
+            })
+            .inspect_err(|error| {
+                if matches!(error, Error::NoSolution) {
+                    self.stats.branches_pruned += 1;
+                }
+            })
+    }
 }
 
 #[inline]
-fn unit_propagate(sudoku: &mut Sudoku, idx: usize) -> Result<()> {
-    for neighbor in consts::NEIGHBORS[idx] {
-        let current_digit = sudoku.digits[neighbor];
-        let bitboard = sudoku.bitboard[neighbor];
+fn unit_propagate<B: Board>(sudoku: &mut B, idx: usize) -> Result<()> {
+    let shape = sudoku.shape().clone();
+    for &neighbor in shape.neighbors(idx) {
+        let current_digit = sudoku.digit(neighbor);
+        let bitboard = sudoku.candidates(neighbor);
         if current_digit == 0 && bitboard == 0 {
-            return Err(Error::from(sudoku));
-        } else if current_digit == 0 && bitboard.count_ones() == 1 {
-            let digit = sudoku.bitboard[neighbor].trailing_zeros() as consts::BitWidth;
+            return Err(Error::NoSolution);
+        } else if current_digit == 0 && bitboard.is_power_of_two() {
+            let digit = bitboard.trailing_zeros() as consts::BitWidth;
             place_and_propagate(sudoku, neighbor, digit)?;
         }
     }
     Ok(())
 }
 
-fn get_next_idx(sudoku: &Sudoku) -> Option<usize> {
-    (0..consts::SIZE)
-        .filter(|&i| sudoku.digits[i] == 0)
-        .map(|i| (i, sudoku.bitboard[i].count_ones()))
-        .min_by_key(|&(_, num_possibilities)| num_possibilities)
-        .map(|(idx, _)| idx)
-}
-
 #[cfg(test)]
 #[allow(clippy::panic_in_result_fn)]
 mod tests {
     use crate::solver;
     use crate::sudoku::Sudoku;
-    use crate::Result;
+    #[cfg(feature = "std")]
+    use crate::{
+        technique::{ForcingChains, HiddenSingles, VisibleSingles},
+        CompactSudoku,
+    };
+    use crate::{Pipeline, Result, Status};
     use rstest::rstest;
     use std::str::FromStr;
 
@@ -160,14 +286,19 @@ mod tests {
         "........8..3...4...9..2..6.....79.......612...6.5.2.7...8...5...1.....2.4.5.....3",
         "621943758783615492594728361142879635357461289869532174238197546916354827475286913"
     )]
-    #[ignore]
+    #[ignore = "the empty board has multiple solutions with `all_solutions`"]
     #[case(
         ".................................................................................",
         "123456789456789123789123456231674895875912364694538217317265948542897631968341572"
     )]
     fn test_sudokus(#[case] input: &str, #[case] expected: &str) -> Result<()> {
         let sudoku = Sudoku::from_str(input)?;
-        let solution = solver::solve(sudoku)?;
+        #[cfg(feature = "std")]
+        assert_eq!(
+            solver::solve(CompactSudoku::from(&sudoku))?.0.to_string(),
+            expected
+        );
+        let (solution, _) = solver::solve(sudoku)?;
         assert_eq!(solution.to_string(), expected);
         Ok(())
     }
@@ -176,23 +307,129 @@ mod tests {
     #[case(
         "057000300000801000001000000600030090020070000800000000400600000000000207000000050",
         "957264381346851972281793645614532798529478136873916524435627819198345267762189453",
+        3,
         3
     )]
     #[case(
         "000000036030000050200000000000060800700000400000053000000700210060900000001000000",
         "148572936637894152295631748314267895756189423829453671583746219462915387971328564",
-        318
+        318,
+        462
     )]
     fn test_manual(
         #[case] input: &str,
         #[case] expected: &str,
-        #[case] expected_recursions: i32,
+        #[case] expected_recursions: u64,
+        #[case] expected_recursions_all_solutions: u64,
     ) -> Result<()> {
         let sudoku = Sudoku::from_str(input)?;
-        let solution = solver::solve(sudoku)?;
+        let (solution, stats) = solver::solve(sudoku)?;
         assert_eq!(&solution.to_string(), expected);
-        assert_eq!(expected_recursions, solution.num_recursions);
+        // with `all_solutions` the search continues to rule out a second solution
+        if cfg!(feature = "all_solutions") {
+            assert_eq!(expected_recursions_all_solutions, stats.num_recursions);
+        } else {
+            assert_eq!(expected_recursions, stats.num_recursions);
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(Pipeline::default())]
+    #[case(Pipeline::empty())]
+    #[cfg_attr(
+        feature = "std",
+        case(Pipeline::empty().with(HiddenSingles).with(VisibleSingles))
+    )]
+    fn test_solve_with(#[case] pipeline: Pipeline) -> Result<()> {
+        let sudoku = Sudoku::from_str(
+            "000000036030000050200000000000060800700000400000053000000700210060900000001000000",
+        )?;
+        let (solution, _) = solver::solve_with(sudoku, &pipeline)?;
+        assert_eq!(
+            solution.to_string(),
+            "148572936637894152295631748314267895756189423829453671583746219462915387971328564"
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "057000300000801000001000000600030090020070000800000000400600000000000207000000050",
+        Pipeline::default(),
+        Status::Solved
+    )]
+    #[case(
+        "000000036030000050200000000000060800700000400000053000000700210060900000001000000",
+        Pipeline::default(),
+        Status::Stuck
+    )]
+    #[cfg_attr(
+        feature = "std",
+        case(
+            "000000036030000050200000000000060800700000400000053000000700210060900000001000000",
+            Pipeline::default().with(ForcingChains),
+            Status::Solved
+        )
+    )]
+    #[case(
+        "12345678.........9...............................................................",
+        Pipeline::default(),
+        Status::Contradiction
+    )]
+    fn test_solve_logically(
+        #[case] input: &str,
+        #[case] pipeline: Pipeline,
+        #[case] expected: Status,
+    ) -> Result<()> {
+        let sudoku = Sudoku::from_str(input)?;
+        let (_, status) = solver::solve_logically(sudoku, &pipeline);
+        assert_eq!(status, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("057000300000801000001000000600030090020070000800000000400600000000000207000000050")]
+    #[case("000000036030000050200000000000060800700000400000053000000700210060900000001000000")]
+    fn test_stats(#[case] input: &str) -> Result<()> {
+        let sudoku = Sudoku::from_str(input)?;
+        let givens = sudoku.num_digits();
+        let (_, stats) = solver::solve(sudoku)?;
+
+        assert!(stats.total().placements as usize >= 81 - givens);
+        assert!(stats.propagation.placements > 0);
+        assert!(stats.max_depth > 0);
+        assert!(stats.branches_pruned > 0);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(42)]
+    fn test_solve_random(#[case] seed: u64) -> Result<()> {
+        let (grid, _) = solver::solve_random(Sudoku::default(), seed)?;
+        let (again, _) = solver::solve_random(Sudoku::default(), seed)?;
+        let (other, _) = solver::solve_random(Sudoku::default(), seed + 1)?;
+
+        assert!(grid.is_solved());
+        assert_eq!(grid.to_string(), again.to_string());
+        assert_ne!(grid.to_string(), other.to_string());
+        let (solution, _) = solver::solve(grid.clone())?;
+        assert_eq!(solution.to_string(), grid.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_random_keeps_givens() -> Result<()> {
+        let sudoku = Sudoku::from_str(
+            "057000300000801000001000000600030090020070000800000000400600000000000207000000050",
+        )?;
+        let (solution, _) = solver::solve(sudoku.clone())?;
+        let (random, _) = solver::solve_random(sudoku, 7)?;
 
+        assert_eq!(random.to_string(), solution.to_string());
         Ok(())
     }
 }
@@ -0,0 +1,76 @@
+use core::ops::AddAssign;
+
+use crate::board::Board;
+
+/// The work done by a single technique. Placements and eliminations made by
+/// the propagation that a technique triggers are counted towards it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TechniqueStats {
+    pub placements: u64,
+    /// Candidates removed, not counting the placed digits themselves.
+    pub eliminations: u64,
+}
+
+impl AddAssign for TechniqueStats {
+    fn add_assign(&mut self, other: Self) {
+        self.placements += other.placements;
+        self.eliminations += other.eliminations;
+    }
+}
+
+impl TechniqueStats {
+    /// The changes made to `board` since it had `num_digits` digits placed
+    /// and `num_removed` candidates removed.
+    #[must_use]
+    pub fn since<B: Board>(board: &B, num_digits: usize, num_removed: u64) -> Self {
+        let placements = (board.num_digits() - num_digits) as u64;
+        Self {
+            placements,
+            eliminations: (board.num_removed() - num_removed).saturating_sub(placements),
+        }
+    }
+}
+
+/// Statistics collected while solving a Sudoku.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    pub num_recursions: u64,
+    /// Cells branched on with more than one candidate.
+    pub guesses: u64,
+    pub visible_singles: TechniqueStats,
+    pub hidden_singles: TechniqueStats,
+    pub pointing: TechniqueStats,
+    pub doubles: TechniqueStats,
+    /// Placing a guessed digit and everything that follows directly from it.
+    pub propagation: TechniqueStats,
+    /// Guessed digits that turned out to have no solution.
+    pub branches_pruned: u64,
+    pub max_depth: u32,
+}
+
+impl AddAssign for SolveStats {
+    fn add_assign(&mut self, other: Self) {
+        self.num_recursions += other.num_recursions;
+        self.guesses += other.guesses;
+        self.visible_singles += other.visible_singles;
+        self.hidden_singles += other.hidden_singles;
+        self.pointing += other.pointing;
+        self.doubles += other.doubles;
+        self.propagation += other.propagation;
+        self.branches_pruned += other.branches_pruned;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+}
+
+impl SolveStats {
+    /// The total number of placements and eliminations made by all techniques.
+    #[must_use]
+    pub fn total(&self) -> TechniqueStats {
+        let mut total = self.visible_singles;
+        total += self.hidden_singles;
+        total += self.pointing;
+        total += self.doubles;
+        total += self.propagation;
+        total
+    }
+}
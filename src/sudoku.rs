@@ -1,36 +1,49 @@
-use std::{fmt, str::FromStr};
+use core::{
+    fmt::{self, Write},
+    str::FromStr,
+};
 
-use crate::{consts, error::Error};
+use crate::{
+    board::Board,
+    consts,
+    error::Error,
+    shape::{Classic, Shape},
+    Result,
+};
 
+/// The character for a digit: 1 to 9, then A to Z for 10 and up.
+pub fn digit_char(digit: consts::BitWidth) -> char {
+    u32::try_from(digit)
+        .ok()
+        .and_then(|digit| char::from_digit(digit, 36))
+        .map_or('.', |c| c.to_ascii_uppercase())
+}
+
+/// A Sudoku of any [`Shape`], the classic 9x9 grid unless stated otherwise.
 #[derive(Debug, Clone)]
-pub struct Sudoku {
-    pub bitboard: [consts::BitWidth; consts::SIZE],
-    pub digits: [consts::BitWidth; consts::SIZE],
+pub struct Sudoku<S: Shape = Classic> {
+    pub bitboard: S::Cells,
+    pub digits: S::Cells,
     num_digits: usize,
-    pub num_recursions: i32,
-    pub guesses: i32,
+    /// The number of candidates removed so far, including the remaining
+    /// candidates of cells that got a digit placed.
+    num_removed: u64,
+    shape: S,
 }
 
-impl fmt::Display for Sudoku {
+impl<S: Shape> fmt::Display for Sudoku<S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let s: String = self
-            .digits
+        self.digits
+            .as_ref()
             .iter()
-            .map(|&digit| {
-                u32::try_from(digit)
-                    .ok()
-                    .and_then(|d| char::from_digit(d, 10))
-                    .unwrap_or('.')
-            })
-            .collect();
-        write!(f, "{s}")
+            .try_for_each(|&digit| f.write_char(digit_char(digit)))
     }
 }
 
 impl FromStr for Sudoku {
     type Err = Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> Result<Self> {
         let mut sudoku = Self::default();
 
         for (i, c) in s.chars().enumerate() {
@@ -45,46 +58,149 @@ impl FromStr for Sudoku {
 
 impl Default for Sudoku {
     fn default() -> Self {
+        Self::new(&Classic::default())
+    }
+}
+
+impl<S: Shape> Sudoku<S> {
+    /// An empty grid of the given shape.
+    #[must_use]
+    pub fn new(shape: &S) -> Self {
         Self {
-            bitboard: [consts::MASK; consts::SIZE],
-            digits: [0; consts::SIZE],
+            bitboard: shape.cells(shape.mask()),
+            digits: shape.cells(0),
             num_digits: 0,
-            num_recursions: 0,
-            guesses: 0,
+            num_removed: 0,
+            shape: shape.clone(),
         }
     }
-}
 
-impl Sudoku {
+    /// Parses one character per cell, row by row. Empty cells are `.` or
+    /// `0`, digits above 9 are written `A` (10) to `P` (25), and whitespace
+    /// is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of cells or a digit doesn't fit the
+    /// shape, or if two givens clash.
+    pub fn parse(shape: &S, s: &str) -> Result<Self> {
+        let mut sudoku = Self::new(shape);
+        let mut cells = s.chars().filter(|c| !c.is_whitespace());
+        for idx in 0..shape.size() {
+            let c = cells.next().ok_or(Error::ParseError)?;
+            if c == '.' || c == '0' {
+                continue;
+            }
+            let digit = c
+                .to_digit(36)
+                .filter(|&digit| digit as usize <= shape.width())
+                .ok_or(Error::ParseError)? as consts::BitWidth;
+            if sudoku.bitboard[idx] & (1 << digit) == 0 {
+                return Err(Error::NoSolution);
+            }
+            sudoku.place(idx, digit);
+        }
+        if cells.next().is_some() {
+            return Err(Error::ParseError);
+        }
+        Ok(sudoku)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn shape(&self) -> &S {
+        &self.shape
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_solved(&self) -> bool {
+        self.num_digits == self.shape.size()
+    }
+
+    /// The number of cells with a placed digit.
     #[inline]
     #[must_use]
-    pub const fn is_solved(&self) -> bool {
-        self.num_digits == consts::SIZE
+    pub const fn num_digits(&self) -> usize {
+        self.num_digits
     }
 
     #[inline]
     pub fn place(&mut self, idx: usize, digit: consts::BitWidth) {
+        self.num_removed += u64::from(self.bitboard[idx].count_ones());
         self.digits[idx] = digit;
         self.bitboard[idx] = 0;
         self.num_digits += 1;
-        let mask = consts::MASK ^ (1 << digit);
+        let bit = 1 << digit;
 
-        for neighbor in consts::NEIGHBORS[idx] {
-            self.bitboard[neighbor] &= mask;
+        for &neighbor in self.shape.neighbors(idx) {
+            if self.bitboard[neighbor] & bit != 0 {
+                self.num_removed += 1;
+                self.bitboard[neighbor] ^= bit;
+            }
         }
     }
 
+    /// The number of candidates removed from the board since it was created.
     #[inline]
-    pub(crate) fn store_stats(&mut self, error: Error) -> Error {
-        if let Error::NoSolution {
-            num_recursions,
-            guesses,
-        } = error
-        {
-            self.num_recursions = num_recursions;
-            self.guesses = guesses;
+    #[must_use]
+    pub const fn num_removed(&self) -> u64 {
+        self.num_removed
+    }
+
+    /// Removes every candidate of the cell that is not in `mask`.
+    #[inline]
+    pub fn keep_candidates(&mut self, idx: usize, mask: consts::BitWidth) {
+        let removed = self.bitboard[idx] & !mask;
+        if removed == 0 {
+            return;
         }
-        error
+        self.num_removed += u64::from(removed.count_ones());
+        self.bitboard[idx] ^= removed;
+    }
+}
+
+impl<S: Shape> Board for Sudoku<S> {
+    type Shape = S;
+
+    #[inline]
+    fn shape(&self) -> &S {
+        &self.shape
+    }
+
+    #[inline]
+    fn digit(&self, idx: usize) -> consts::BitWidth {
+        self.digits[idx]
+    }
+
+    #[inline]
+    fn candidates(&self, idx: usize) -> consts::BitWidth {
+        self.bitboard[idx]
+    }
+
+    #[inline]
+    fn place(&mut self, idx: usize, digit: consts::BitWidth) {
+        Self::place(self, idx, digit);
+    }
+
+    #[inline]
+    fn keep_candidates(&mut self, idx: usize, mask: consts::BitWidth) {
+        Self::keep_candidates(self, idx, mask);
+    }
+
+    #[inline]
+    fn num_digits(&self) -> usize {
+        self.num_digits
+    }
+
+    #[inline]
+    fn num_removed(&self) -> u64 {
+        self.num_removed
+    }
+
+    #[inline]
+    fn is_solved(&self) -> bool {
+        Self::is_solved(self)
     }
 }
 
@@ -93,8 +209,60 @@ impl Sudoku {
 mod tests {
     use super::super::*;
     use super::*;
+    use crate::shape::Boxes;
     use rstest::rstest;
 
+    /// Every unit holds every digit exactly once, and the givens are kept.
+    fn assert_valid<S: Shape>(solution: &Sudoku<S>, puzzle: &Sudoku<S>) {
+        let shape = solution.shape();
+        assert!(solution.is_solved());
+        for unit in 0..shape.num_units() {
+            let mut seen: Vec<_> = shape
+                .unit(unit)
+                .iter()
+                .map(|&idx| solution.digits[idx])
+                .collect();
+            seen.sort_unstable();
+            assert_eq!(seen, (1..=shape.width()).collect::<Vec<_>>());
+        }
+        for idx in 0..shape.size() {
+            for &other in shape.neighbors(idx) {
+                assert_ne!(solution.digits[idx], solution.digits[other]);
+            }
+        }
+        for idx in (0..shape.size()).filter(|&idx| puzzle.digits[idx] != 0) {
+            assert_eq!(solution.digits[idx], puzzle.digits[idx]);
+        }
+    }
+
+    /// A valid solution with every third cell left empty.
+    fn pattern_puzzle(box_height: usize, box_width: usize) -> String {
+        let width = box_height * box_width;
+        (0..width * width)
+            .map(|idx| {
+                let (row, col) = (idx / width, idx % width);
+                let digit = (row % box_height * box_width + row / box_height + col) % width + 1;
+                if idx % 3 == 0 {
+                    '.'
+                } else {
+                    digit_char(digit)
+                }
+            })
+            .collect()
+    }
+
+    fn assert_solves_pattern<S: Shape>(
+        shape: &S,
+        box_height: usize,
+        box_width: usize,
+    ) -> Result<()> {
+        let puzzle = Sudoku::parse(shape, &pattern_puzzle(box_height, box_width))?;
+        let (solution, _) = solver::solve_random(puzzle.clone(), 0)?;
+
+        assert_valid(&solution, &puzzle);
+        Ok(())
+    }
+
     #[rstest]
     #[case(
         ".................................................................................",
@@ -132,10 +300,199 @@ mod tests {
         #[case] is_solved: bool,
     ) -> Result<()> {
         let mut sudoku = Sudoku::from_str(input)?;
-
         sudoku.place(idx, digit);
         assert_eq!(sudoku.to_string(), expected);
         assert_eq!(sudoku.is_solved(), is_solved);
+
+        #[cfg(feature = "std")]
+        {
+            let mut compact = CompactSudoku::from_str(input)?;
+            compact.place(idx, digit);
+            assert_eq!(compact.to_string(), expected);
+            assert_eq!(compact.is_solved(), is_solved);
+        }
+        Ok(())
+    }
+    #[rstest]
+    #[case("1.....2..3....4.", "1234342143122143")]
+    #[case("..3...4..1...4..", "4231134231242413")]
+    fn test_4x4(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let (solution, _) = solver::solve(Sudoku::parse(&Boxes::<2, 2>, input)?)?;
+
+        assert_eq!(solution.to_string(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_6x6() -> Result<()> {
+        let puzzle = Sudoku::parse(&Boxes::<2, 3>, "1.4.5.........65.1....4...521.2.....")?;
+        let (solution, _) = solver::solve(puzzle)?;
+
+        assert_eq!(solution.to_string(), "134652652134346521521346465213213465");
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_solution() -> Result<()> {
+        let puzzle = Sudoku::parse(&Boxes::<2, 2>, "4..1.......3.2..")?;
+        assert!(solver::solve(puzzle).is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_pattern() -> Result<()> {
+        assert_solves_pattern(&Boxes::<2, 2>, 2, 2)?;
+        assert_solves_pattern(&Boxes::<3, 3>, 3, 3)?;
+        assert_solves_pattern(&Boxes::<4, 4>, 4, 4)?;
+        assert_solves_pattern(&Boxes::<5, 5>, 5, 5)?;
+        assert_solves_pattern(&Boxes::<2, 3>, 2, 3)?;
+        assert_solves_pattern(&Boxes::<2, 4>, 2, 4)?;
+        assert_solves_pattern(&Boxes::<3, 4>, 3, 4)?;
+        assert_solves_pattern(&Boxes::<4, 3>, 4, 3)
+    }
+
+    #[rstest]
+    #[case("1.....2..3....4")]
+    #[case("1.....2..3....4..")]
+    #[case("1.....2..3....5.")]
+    #[case("11..............")]
+    fn test_parse_errors(#[case] input: &str) {
+        assert!(Sudoku::parse(&Boxes::<2, 2>, input).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    mod geometry {
+        use super::*;
+        use crate::{ChessMove, Geometry};
+
+        #[rstest]
+        #[case("000000010400000000020000000000050407008000300001090000300400200050100000000806000")]
+        #[case("........8..3...4...9..2..6.....79.......612...6.5.2.7...8...5...1.....2.4.5.....3")]
+        fn test_matches_classic(#[case] input: &str) -> Result<()> {
+            let geometry = Geometry::square(3)?;
+            let (solution, _) = solver::solve(Sudoku::parse(&geometry, input)?)?;
+            let (expected, _) = solver::solve(Sudoku::from_str(input)?)?;
+
+            assert_eq!(solution.to_string(), expected.to_string());
+            Ok(())
+        }
+
+        #[rstest]
+        #[case(2, 3)]
+        #[case(3, 4)]
+        fn test_pattern(#[case] box_height: usize, #[case] box_width: usize) -> Result<()> {
+            let geometry = Geometry::rectangular(box_height, box_width)?;
+            assert_solves_pattern(&geometry, box_height, box_width)
+        }
+
+        #[test]
+        fn test_jigsaw() -> Result<()> {
+            let layout =
+                "000011222001111122300112222304445555334444485333338485666688885666677785677777785";
+            let regions: Vec<usize> = layout
+                .chars()
+                .filter_map(|c| c.to_digit(10))
+                .map(|region| region as usize)
+                .collect();
+            let geometry = Geometry::jigsaw(&regions)?;
+            let puzzle = Sudoku::parse(
+                &geometry,
+                "......5.8.........1...5.3......7........8.67......4...42....1....6.........6..4.7",
+            )?;
+            let (solution, _) = solver::solve(puzzle.clone())?;
+
+            assert_valid(&solution, &puzzle);
+            assert_eq!(
+                solution.to_string(),
+                "793412568257346819168957342314579286941283675675824931429768153836195724582631497"
+            );
+            Ok(())
+        }
+
+        #[rstest]
+        #[case(
+            "diagonals",
+            "......7..8...46....4......56......57...9..36..8...........1........5......8..32.4",
+            "132895746895746132746132895629381457517924368483567921364218579271459683958673214"
+        )]
+        #[case(
+            "windoku",
+            "...8.....8...4.....4......95......7.......26............3.7............8..152...7",
+            "132895746895746132746132859528364971374981265619257483283679514957413628461528397"
+        )]
+        #[case(
+            "disjoint",
+            "...8..7..8...46....4..3...53......69..9...25............3.5........6......748.6.3",
+            "132895746895746132746132895321578469479613258568924371613257984984361527257489613"
+        )]
+        fn test_extra_units(
+            #[case] extras: &str,
+            #[case] input: &str,
+            #[case] expected: &str,
+        ) -> Result<()> {
+            let geometry = Geometry::square(3)?.with_extras(extras)?;
+            let puzzle = Sudoku::parse(&geometry, input)?;
+            let (solution, _) = solver::solve(puzzle.clone())?;
+
+            assert_valid(&solution, &puzzle);
+            assert_eq!(solution.to_string(), expected);
+            Ok(())
+        }
+
+        #[rstest]
+        #[case(
+            ChessMove::Knight,
+            "........6......4..........3...6.3...8..............7.95..........2....47....3....",
+            "793451826628379451415862973957623184834197562261584739579246318382915647146738295"
+        )]
+        #[case(
+            ChessMove::King,
+            "7.......68..96.7..........5.7.5.4...3...........89...41.....6....8....53..2......",
+            "793451826825963741416728935279534168384617592561892374137245689648179253952386417"
+        )]
+        fn test_chess_moves(
+            #[case] chess_move: ChessMove,
+            #[case] input: &str,
+            #[case] expected: &str,
+        ) -> Result<()> {
+            let geometry = Geometry::square(3)?.with_chess_move(chess_move);
+            let puzzle = Sudoku::parse(&geometry, input)?;
+            let (solution, _) = solver::solve(puzzle.clone())?;
+
+            assert_valid(&solution, &puzzle);
+            assert_eq!(solution.to_string(), expected);
+            Ok(())
+        }
+
+        #[test]
+        fn test_place_with_chess_move() -> Result<()> {
+            let plain = Geometry::square(3)?;
+            let mut knight = Sudoku::new(&plain.clone().with_chess_move(ChessMove::Knight));
+            let mut sudoku = Sudoku::new(&plain);
+            knight.place(40, 5);
+            sudoku.place(40, 5);
+
+            // the cells a knight's move away from r5c5 lose the 5 as well
+            for idx in [21, 23, 29, 33, 47, 51, 57, 59] {
+                assert_eq!(knight.bitboard[idx] & 1 << 5, 0);
+                assert_ne!(sudoku.bitboard[idx] & 1 << 5, 0);
+            }
+            assert_eq!(knight.num_removed(), sudoku.num_removed() + 8);
+            Ok(())
+        }
+
+        #[test]
+        fn test_hexadoku_from_empty() -> Result<()> {
+            let geometry = Geometry::square(4)?;
+            let puzzle = Sudoku::new(&geometry);
+            let (solution, _) = solver::solve_random(puzzle.clone(), 0)?;
+
+            assert_valid(&solution, &puzzle);
+            assert_eq!(
+                Sudoku::parse(&geometry, &solution.to_string())?.to_string(),
+                solution.to_string()
+            );
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,387 @@
+#[cfg(feature = "std")]
+use crate::constraint::Constraint;
+#[cfg(feature = "std")]
+use crate::{
+    aic::apply_aic,
+    als::apply_als_step,
+    chains::apply_x_chain,
+    coloring::apply_coloring,
+    forcing::apply_forcing_chain,
+    uniqueness::{apply_deadly_pattern, Uniqueness},
+    wings::apply_wing,
+};
+use crate::{
+    board::Board,
+    hidden::check_all_hidden_zeroes,
+    stats::{SolveStats, TechniqueStats},
+    sudoku::Sudoku,
+    triples::check_triples,
+    visible::{check_all_visible_doubles, place_all_visible_singles},
+    Result,
+};
+
+/// Whether applying a technique changed the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    Unchanged,
+    Changed,
+}
+
+impl From<bool> for Progress {
+    fn from(changed: bool) -> Self {
+        if changed {
+            Self::Changed
+        } else {
+            Self::Unchanged
+        }
+    }
+}
+
+/// A single solving technique that can be run as part of a
+/// [`Pipeline`](crate::Pipeline).
+///
+/// Techniques work on a 9x9 [`Sudoku`] unless they are implemented for every
+/// [`Board`], like the singles, pointing and doubles.
+pub trait Technique<B = Sudoku>: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// How hard the technique is for a human to spot. Higher is harder, and the
+    /// built-in techniques roughly follow the Sudoku Explainer scale times ten.
+    fn difficulty(&self) -> u32;
+
+    /// Apply the technique to the board.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board is invalid.
+    fn apply(&self, board: &mut B) -> Result<Progress>;
+
+    /// The entry in [`SolveStats`] that this technique's work is counted
+    /// towards, if any.
+    fn stats<'a>(&self, _stats: &'a mut SolveStats) -> Option<&'a mut TechniqueStats> {
+        None
+    }
+}
+
+/// Runs a technique that modifies the board in place, and reports whether any
+/// candidate was removed.
+#[inline]
+fn track<B: Board>(
+    board: &mut B,
+    technique: impl FnOnce(&mut B) -> Result<()>,
+) -> Result<Progress> {
+    let num_removed = board.num_removed();
+    technique(board)?;
+    Ok(Progress::from(board.num_removed() != num_removed))
+}
+
+/// Applies the technique, and adds the placements and eliminations it made to
+/// its entry in `stats`, if it has one.
+///
+/// # Errors
+///
+/// Returns an error if the board is invalid.
+#[inline]
+pub fn apply_counting<B: Board>(
+    technique: &dyn Technique<B>,
+    board: &mut B,
+    stats: &mut SolveStats,
+) -> Result<Progress> {
+    let num_digits = board.num_digits();
+    let num_removed = board.num_removed();
+    let progress = technique.apply(board)?;
+    if progress == Progress::Changed {
+        if let Some(technique_stats) = technique.stats(stats) {
+            *technique_stats += TechniqueStats::since(board, num_digits, num_removed);
+        }
+    }
+    Ok(progress)
+}
+
+/// Places every cell with a single candidate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VisibleSingles;
+
+impl<B: Board> Technique<B> for VisibleSingles {
+    fn name(&self) -> &'static str {
+        "Visible Singles"
+    }
+
+    fn difficulty(&self) -> u32 {
+        23
+    }
+
+    fn apply(&self, board: &mut B) -> Result<Progress> {
+        track(board, place_all_visible_singles)
+    }
+
+    fn stats<'a>(&self, stats: &'a mut SolveStats) -> Option<&'a mut TechniqueStats> {
+        Some(&mut stats.visible_singles)
+    }
+}
+
+/// Places every digit that has a single position in a unit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HiddenSingles;
+
+impl<B: Board> Technique<B> for HiddenSingles {
+    fn name(&self) -> &'static str {
+        "Hidden Singles"
+    }
+
+    fn difficulty(&self) -> u32 {
+        12
+    }
+
+    fn apply(&self, board: &mut B) -> Result<Progress> {
+        track(board, B::place_hidden_singles)
+    }
+
+    fn stats<'a>(&self, stats: &'a mut SolveStats) -> Option<&'a mut TechniqueStats> {
+        Some(&mut stats.hidden_singles)
+    }
+}
+
+/// Fails if a digit has no position left in a unit. Never changes the board.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HiddenZeroes;
+
+impl<B: Board> Technique<B> for HiddenZeroes {
+    fn name(&self) -> &'static str {
+        "Hidden Zeroes"
+    }
+
+    fn difficulty(&self) -> u32 {
+        0
+    }
+
+    fn apply(&self, board: &mut B) -> Result<Progress> {
+        check_all_hidden_zeroes(board)?;
+        Ok(Progress::Unchanged)
+    }
+}
+
+/// Removes a digit from a line when it is confined to that line within a box.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Triples;
+
+impl<B: Board> Technique<B> for Triples {
+    fn name(&self) -> &'static str {
+        "Triples"
+    }
+
+    fn difficulty(&self) -> u32 {
+        26
+    }
+
+    fn apply(&self, board: &mut B) -> Result<Progress> {
+        track(board, check_triples)
+    }
+
+    fn stats<'a>(&self, stats: &'a mut SolveStats) -> Option<&'a mut TechniqueStats> {
+        Some(&mut stats.pointing)
+    }
+}
+
+/// Removes the digits of two identical bivalue cells from the rest of a unit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VisibleDoubles;
+
+impl<B: Board> Technique<B> for VisibleDoubles {
+    fn name(&self) -> &'static str {
+        "Visible Doubles"
+    }
+
+    fn difficulty(&self) -> u32 {
+        30
+    }
+
+    fn apply(&self, board: &mut B) -> Result<Progress> {
+        track(board, check_all_visible_doubles)
+    }
+
+    fn stats<'a>(&self, stats: &'a mut SolveStats) -> Option<&'a mut TechniqueStats> {
+        Some(&mut stats.doubles)
+    }
+}
+
+/// Applies the first XY-Wing, XYZ-Wing or W-Wing found.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Wings;
+
+#[cfg(feature = "std")]
+impl Technique for Wings {
+    fn name(&self) -> &'static str {
+        "Wings"
+    }
+
+    fn difficulty(&self) -> u32 {
+        42
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<Progress> {
+        Ok(Progress::from(apply_wing(sudoku)?.is_some()))
+    }
+}
+
+/// Applies the first unique rectangle or BUG+1 found. Does nothing unless the
+/// puzzle is assumed to have a single solution.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeadlyPatterns(pub Uniqueness);
+
+#[cfg(feature = "std")]
+impl Technique for DeadlyPatterns {
+    fn name(&self) -> &'static str {
+        "Deadly Patterns"
+    }
+
+    fn difficulty(&self) -> u32 {
+        45
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<Progress> {
+        Ok(Progress::from(
+            apply_deadly_pattern(sudoku, self.0)?.is_some(),
+        ))
+    }
+}
+
+/// Applies the first color trap or color wrap found.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Colorings;
+
+#[cfg(feature = "std")]
+impl Technique for Colorings {
+    fn name(&self) -> &'static str {
+        "Simple Coloring"
+    }
+
+    fn difficulty(&self) -> u32 {
+        60
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<Progress> {
+        Ok(Progress::from(apply_coloring(sudoku)?.is_some()))
+    }
+}
+
+/// Applies the shortest X-Chain found.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XChains;
+
+#[cfg(feature = "std")]
+impl Technique for XChains {
+    fn name(&self) -> &'static str {
+        "X-Chains"
+    }
+
+    fn difficulty(&self) -> u32 {
+        65
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<Progress> {
+        Ok(Progress::from(apply_x_chain(sudoku)?.is_some()))
+    }
+}
+
+/// Applies the shortest XY-Chain, or failing that the shortest AIC, found.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Aics;
+
+#[cfg(feature = "std")]
+impl Technique for Aics {
+    fn name(&self) -> &'static str {
+        "Alternating Inference Chains"
+    }
+
+    fn difficulty(&self) -> u32 {
+        70
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<Progress> {
+        Ok(Progress::from(apply_aic(sudoku)?.is_some()))
+    }
+}
+
+/// Applies the first ALS-XZ, ALS-XY-Wing or Sue de Coq found.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlmostLockedSets;
+
+#[cfg(feature = "std")]
+impl Technique for AlmostLockedSets {
+    fn name(&self) -> &'static str {
+        "Almost Locked Sets"
+    }
+
+    fn difficulty(&self) -> u32 {
+        75
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<Progress> {
+        Ok(Progress::from(apply_als_step(sudoku)?.is_some()))
+    }
+}
+
+/// Applies the first Nishio, cell, unit or digit forcing chain found.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ForcingChains;
+
+#[cfg(feature = "std")]
+impl Technique for ForcingChains {
+    fn name(&self) -> &'static str {
+        "Forcing Chains"
+    }
+
+    fn difficulty(&self) -> u32 {
+        85
+    }
+
+    fn apply(&self, sudoku: &mut Sudoku) -> Result<Progress> {
+        Ok(Progress::from(apply_forcing_chain(sudoku)?.is_some()))
+    }
+}
+
+/// A constraint runs like a technique, so that it can be added to a
+/// [`Pipeline`](crate::Pipeline) after the techniques that only look at the
+/// units.
+#[cfg(feature = "std")]
+impl<B: Board> Technique<B> for Box<dyn Constraint<B>> {
+    fn name(&self) -> &'static str {
+        "Constraint"
+    }
+
+    /// Constraints are rules of the puzzle, which a human doesn't need to spot.
+    fn difficulty(&self) -> u32 {
+        0
+    }
+
+    fn apply(&self, board: &mut B) -> Result<Progress> {
+        self.propagate(board)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_progress() -> Result<()> {
+        let mut sudoku = Sudoku::from_str(
+            "12345678.........................................................................",
+        )?;
+        assert_eq!(VisibleSingles.apply(&mut sudoku)?, Progress::Changed);
+        assert_eq!(sudoku.digits[8], 9);
+        assert_eq!(VisibleSingles.apply(&mut sudoku)?, Progress::Unchanged);
+        assert_eq!(HiddenZeroes.apply(&mut sudoku)?, Progress::Unchanged);
+        Ok(())
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{consts, solver::place_and_propagate, sudoku::Sudoku, Error, Result};
+use crate::{
+    board::Board,
+    consts,
+    shape::{Shape, MAX_WIDTH},
+    solver::place_and_propagate,
+    Error, Result,
+};
 
 /// Check all triples in the Sudoku.
 ///
@@ -6,94 +12,81 @@ use crate::{consts, solver::place_and_propagate, sudoku::Sudoku, Error, Result};
 ///
 /// Returns an error if the Sudoku is invalid.
 #[allow(clippy::module_name_repetitions)]
-pub fn check_triples(sudoku: &mut Sudoku) -> Result<()> {
-    check_triples_rows_or_cols(sudoku, &get_row, &consts::ROWS)?;
-    check_triples_rows_or_cols(sudoku, &get_col, &consts::COLS)
+pub fn check_triples<B: Board>(board: &mut B) -> Result<()> {
+    check_triples_rows_or_cols(board, 0)?;
+    check_triples_rows_or_cols(board, 1)
 }
 
-#[inline]
-const fn get_row(idx: usize) -> usize {
-    idx / consts::WIDTH
-}
-
-#[inline]
-const fn get_col(idx: usize) -> usize {
-    idx % consts::WIDTH
-}
-
-fn check_triples_rows_or_cols(
-    sudoku: &mut Sudoku,
-    get_row_or_col: &dyn Fn(usize) -> usize,
-    cols_or_rows: &[[usize; 9]; 9],
-) -> Result<()> {
-    for (cell_idx, cell) in consts::CELLS.iter().enumerate() {
-        let mut cache = [0; 3];
-        let base_row_idx = get_row_or_col(cell[0]);
-        for &idx in cell {
-            let row = get_row_or_col(idx);
-            cache[row % 3] |= sudoku.bitboard[idx];
+/// Removes a digit from a row (`kind` 0) or column (`kind` 1) when it is
+/// confined to that line within a box.
+fn check_triples_rows_or_cols<B: Board>(board: &mut B, kind: usize) -> Result<()> {
+    let shape = board.shape().clone();
+    let width = shape.width();
+    for cell_idx in 2 * width..3 * width {
+        // the candidates of each line through the box
+        let mut cache = [(0, 0); MAX_WIDTH];
+        let mut num_lines = 0;
+        for &idx in shape.unit(cell_idx) {
+            let line = shape.units_of(idx)[kind];
+            let pos = cache[..num_lines]
+                .iter()
+                .position(|&(other, _)| other == line)
+                .unwrap_or(num_lines);
+            if pos == num_lines {
+                cache[pos].0 = line;
+                num_lines += 1;
+            }
+            cache[pos].1 |= board.candidates(idx);
         }
-        for digit in 1..=9 {
-            check_digit(sudoku, digit, &cache, base_row_idx, cell_idx, cols_or_rows)?;
+        for digit in 1..=width {
+            check_digit(board, &shape, digit, &cache[..num_lines], cell_idx)?;
         }
     }
     Ok(())
 }
 
-fn check_digit(
-    sudoku: &mut Sudoku,
-    digit: i32,
-    cache: &[usize; 3],
-    base_row_idx: usize,
+fn check_digit<B: Board>(
+    board: &mut B,
+    shape: &B::Shape,
+    digit: usize,
+    cache: &[(usize, consts::BitWidth)],
     cell_idx: usize,
-    cols_or_rows: &[[usize; 9]; 9],
 ) -> Result<()> {
     let bitmask = 1 << digit;
-    let matching_rows: Vec<usize> = cache
+    let mut matching_rows = cache
         .iter()
-        .enumerate()
-        .filter(|(_, &row_bitmask)| row_bitmask & bitmask > 0)
-        .map(|(row_idx, _)| row_idx)
-        .collect();
+        .filter(|(_, row_bitmask)| row_bitmask & bitmask > 0)
+        .map(|&(line, _)| line);
 
-    if let [row_idx] = matching_rows.as_slice() {
-        check_triple_digits(
-            sudoku,
-            digit,
-            base_row_idx,
-            *row_idx,
-            cell_idx,
-            cols_or_rows,
-        )?;
+    if let (Some(line), None) = (matching_rows.next(), matching_rows.next()) {
+        check_triple_digits(board, shape, digit, line, cell_idx)?;
     }
     Ok(())
 }
 
-fn check_triple_digits(
-    sudoku: &mut Sudoku,
-    digit: i32,
-    base_row_idx: usize,
-    row_idx: usize,
+fn check_triple_digits<B: Board>(
+    board: &mut B,
+    shape: &B::Shape,
+    digit: usize,
+    line: usize,
     cell_idx: usize,
-    cols_or_rows: &[[usize; 9]; 9],
 ) -> Result<()> {
-    let row_idx = row_idx + base_row_idx;
     let bitmask = 1 << digit;
-    for idx in cols_or_rows[row_idx] {
-        if consts::CELL_LOOKUP[idx] != cell_idx {
-            sudoku.bitboard[idx] &= consts::MASK ^ bitmask;
-            place_triple_digit(sudoku, idx)?;
+    for &idx in shape.unit(line) {
+        if shape.units_of(idx)[2] != cell_idx {
+            board.keep_candidates(idx, shape.mask() ^ bitmask);
+            place_triple_digit(board, idx)?;
         }
     }
     Ok(())
 }
 
-fn place_triple_digit(sudoku: &mut Sudoku, idx: usize) -> Result<()> {
-    if sudoku.digits[idx] == 0 && sudoku.bitboard[idx] == 0 {
-        Err(Error::from(sudoku))
-    } else if sudoku.bitboard[idx].count_ones() == 1 {
-        let digit = sudoku.bitboard[idx].trailing_zeros() as consts::BitWidth;
-        place_and_propagate(sudoku, idx, digit)
+fn place_triple_digit<B: Board>(board: &mut B, idx: usize) -> Result<()> {
+    if board.digit(idx) == 0 && board.candidates(idx) == 0 {
+        Err(Error::NoSolution)
+    } else if board.candidates(idx).is_power_of_two() {
+        let digit = board.candidates(idx).trailing_zeros() as consts::BitWidth;
+        place_and_propagate(board, idx, digit)
     } else {
         Ok(())
     }
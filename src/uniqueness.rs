@@ -0,0 +1,498 @@
+use std::fmt;
+
+use crate::{
+    candidate::{self, sees, subsets, Candidate, CellName},
+    consts,
+    sudoku::Sudoku,
+    Result,
+};
+
+/// Whether the puzzle may be assumed to have a single solution. Uniqueness
+/// based techniques only run when it is `Assumed`, as they are unsound for
+/// puzzles with multiple solutions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Uniqueness {
+    #[default]
+    NotAssumed,
+    Assumed,
+}
+
+#[cfg(feature = "all_solutions")]
+impl Uniqueness {
+    /// Solve the Sudoku to find out whether it has a single solution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Sudoku has no solution.
+    pub fn verify(sudoku: &Sudoku) -> Result<Self> {
+        match crate::solver::solve(sudoku.clone()) {
+            Ok(_) => Ok(Self::Assumed),
+            Err(crate::Error::MultipleSolutions) => Ok(Self::NotAssumed),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadlyPatternKind {
+    UniqueRectangle1,
+    UniqueRectangle2,
+    UniqueRectangle3,
+    UniqueRectangle4,
+    HiddenRectangle,
+    BugPlusOne,
+}
+
+impl fmt::Display for DeadlyPatternKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UniqueRectangle1 => write!(f, "Unique Rectangle Type 1"),
+            Self::UniqueRectangle2 => write!(f, "Unique Rectangle Type 2"),
+            Self::UniqueRectangle3 => write!(f, "Unique Rectangle Type 3"),
+            Self::UniqueRectangle4 => write!(f, "Unique Rectangle Type 4"),
+            Self::HiddenRectangle => write!(f, "Hidden Rectangle"),
+            Self::BugPlusOne => write!(f, "BUG+1"),
+        }
+    }
+}
+
+/// A pattern that would allow multiple solutions, together with the
+/// candidates that must be eliminated to avoid it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadlyPattern {
+    pub kind: DeadlyPatternKind,
+    pub digits: consts::BitWidth,
+    pub cells: Vec<usize>,
+    pub eliminations: Vec<Candidate>,
+}
+
+impl fmt::Display for DeadlyPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.kind)?;
+        candidate::digits(self.digits).try_for_each(|digit| write!(f, "{digit}"))?;
+        for (i, &idx) in self.cells.iter().enumerate() {
+            let separator = if i == 0 { " in " } else { "," };
+            write!(f, "{separator}{}", CellName(idx))?;
+        }
+        write!(f, " =>")?;
+        self.eliminations
+            .iter()
+            .try_for_each(|candidate| write!(f, " {candidate}"))
+    }
+}
+
+/// Find all unique rectangles, hidden rectangles and BUG+1 patterns, if
+/// uniqueness is assumed.
+#[must_use]
+pub fn find_deadly_patterns(sudoku: &Sudoku, uniqueness: Uniqueness) -> Vec<DeadlyPattern> {
+    let mut patterns = find_unique_rectangles(sudoku, uniqueness);
+    patterns.extend(find_bug_plus_one(sudoku, uniqueness));
+    patterns
+}
+
+/// Apply the eliminations of the first deadly pattern found, if any.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn apply_deadly_pattern(
+    sudoku: &mut Sudoku,
+    uniqueness: Uniqueness,
+) -> Result<Option<DeadlyPattern>> {
+    let Some(pattern) = find_deadly_patterns(sudoku, uniqueness).into_iter().next() else {
+        return Ok(None);
+    };
+    candidate::eliminate(sudoku, &pattern.eliminations)?;
+    Ok(Some(pattern))
+}
+
+/// Find unique rectangles of type 1 to 4 and hidden rectangles: four unsolved
+/// cells in two rows, two columns and two boxes that share two candidates.
+#[must_use]
+pub fn find_unique_rectangles(sudoku: &Sudoku, uniqueness: Uniqueness) -> Vec<DeadlyPattern> {
+    if uniqueness == Uniqueness::NotAssumed {
+        return vec![];
+    }
+    let mut patterns = vec![];
+    for cells in rectangles() {
+        if cells.iter().any(|&idx| sudoku.digits[idx] != 0) {
+            continue;
+        }
+        let common = cells
+            .iter()
+            .fold(consts::MASK, |mask, &idx| mask & sudoku.bitboard[idx]);
+        for a in candidate::digits(common) {
+            for b in candidate::digits(common).filter(|&b| b > a) {
+                let pair = (1 << a) | (1 << b);
+                check_rectangle(sudoku, cells, [a, b], pair, &mut patterns);
+            }
+        }
+    }
+    patterns
+}
+
+/// Find a bivalue universal grave plus one.
+///
+/// Every unsolved cell has two candidates except for a single cell with three,
+/// and every candidate appears exactly twice in each row, column and box, apart
+/// from one digit that appears three times in the units of that cell. The cell
+/// must be that digit.
+#[must_use]
+pub fn find_bug_plus_one(sudoku: &Sudoku, uniqueness: Uniqueness) -> Option<DeadlyPattern> {
+    if uniqueness == Uniqueness::NotAssumed {
+        return None;
+    }
+    let mut extra = None;
+    for idx in (0..consts::SIZE).filter(|&idx| sudoku.digits[idx] == 0) {
+        match sudoku.bitboard[idx].count_ones() {
+            2 => {}
+            3 if extra.is_none() => extra = Some(idx),
+            _ => return None,
+        }
+    }
+    let idx = extra?;
+    let digit = candidate::digits(sudoku.bitboard[idx]).find(|&digit| {
+        candidate::units()
+            .filter(|unit| unit.contains(&idx))
+            .all(|unit| candidate::cells_with_digit(sudoku, unit, digit).count() == 3)
+    })?;
+    let is_grave = candidate::units().all(|unit| {
+        candidate::digits(consts::MASK).all(|other| {
+            let count = candidate::cells_with_digit(sudoku, unit, other).count();
+            let expected = if other == digit && unit.contains(&idx) {
+                3
+            } else {
+                2
+            };
+            count == 0 || count == expected
+        })
+    });
+    if !is_grave {
+        return None;
+    }
+    let eliminations = candidate::digits(sudoku.bitboard[idx])
+        .filter(|&other| other != digit)
+        .map(|other| Candidate::new(idx, other))
+        .collect();
+    Some(DeadlyPattern {
+        kind: DeadlyPatternKind::BugPlusOne,
+        digits: 1 << digit,
+        cells: vec![idx],
+        eliminations,
+    })
+}
+
+/// All rectangles spanning exactly two boxes, as `[r1c1, r1c2, r2c1, r2c2]`.
+fn rectangles() -> impl Iterator<Item = [usize; 4]> {
+    let pairs = |n: usize| (0..n).flat_map(move |a| (a + 1..n).map(move |b| (a, b)));
+    pairs(consts::WIDTH).flat_map(move |(r1, r2)| {
+        pairs(consts::WIDTH).filter_map(move |(c1, c2)| {
+            let same_band = r1 / 3 == r2 / 3;
+            let same_stack = c1 / 3 == c2 / 3;
+            (same_band != same_stack).then_some([
+                r1 * consts::WIDTH + c1,
+                r1 * consts::WIDTH + c2,
+                r2 * consts::WIDTH + c1,
+                r2 * consts::WIDTH + c2,
+            ])
+        })
+    })
+}
+
+fn check_rectangle(
+    sudoku: &Sudoku,
+    cells: [usize; 4],
+    digits: [consts::BitWidth; 2],
+    pair: consts::BitWidth,
+    patterns: &mut Vec<DeadlyPattern>,
+) {
+    let (floor, roof): (Vec<usize>, Vec<usize>) =
+        cells.iter().partition(|&&idx| sudoku.bitboard[idx] == pair);
+    let mut push = |kind, eliminations: Vec<Candidate>| {
+        if !eliminations.is_empty() {
+            patterns.push(DeadlyPattern {
+                kind,
+                digits: pair,
+                cells: cells.to_vec(),
+                eliminations,
+            });
+        }
+    };
+
+    match (floor.as_slice(), roof.as_slice()) {
+        ([_, _, _], &[roof]) => {
+            let eliminations = digits.iter().map(|&d| Candidate::new(roof, d)).collect();
+            push(DeadlyPatternKind::UniqueRectangle1, eliminations);
+        }
+        ([_, _], &[first, second]) if sees(first, second) => {
+            push(
+                DeadlyPatternKind::UniqueRectangle2,
+                type_2_eliminations(sudoku, [first, second], pair),
+            );
+            push(
+                DeadlyPatternKind::UniqueRectangle3,
+                type_3_eliminations(sudoku, [first, second], pair),
+            );
+            push(
+                DeadlyPatternKind::UniqueRectangle4,
+                type_4_eliminations(sudoku, [first, second], digits),
+            );
+        }
+        (&[corner], _) => {
+            push(
+                DeadlyPatternKind::HiddenRectangle,
+                hidden_rectangle_eliminations(sudoku, cells, corner, digits),
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Both roof cells have the same single extra digit, so one of them is that
+/// digit.
+fn type_2_eliminations(
+    sudoku: &Sudoku,
+    roof: [usize; 2],
+    pair: consts::BitWidth,
+) -> Vec<Candidate> {
+    let extra = sudoku.bitboard[roof[0]] & !pair;
+    if extra != sudoku.bitboard[roof[1]] & !pair || !extra.is_power_of_two() {
+        return vec![];
+    }
+    let digit = extra.trailing_zeros() as consts::BitWidth;
+    candidate::eliminations_seen_by(sudoku, &roof, digit)
+}
+
+/// The extra digits of the roof cells act as a single virtual cell, which can
+/// form a naked subset with other cells in a unit shared by the roof.
+fn type_3_eliminations(
+    sudoku: &Sudoku,
+    roof: [usize; 2],
+    pair: consts::BitWidth,
+) -> Vec<Candidate> {
+    let extra = (sudoku.bitboard[roof[0]] | sudoku.bitboard[roof[1]]) & !pair;
+    let mut eliminations = vec![];
+    for unit in shared_units(roof) {
+        let others: Vec<usize> = unit
+            .iter()
+            .copied()
+            .filter(|idx| !roof.contains(idx) && sudoku.digits[*idx] == 0)
+            .collect();
+        for subset in subsets(&others, 3) {
+            let mask = subset
+                .iter()
+                .fold(extra, |mask, &idx| mask | sudoku.bitboard[idx]);
+            if mask.count_ones() as usize != subset.len() + 1 {
+                continue;
+            }
+            eliminations.extend(others.iter().filter(|idx| !subset.contains(idx)).flat_map(
+                |&idx| {
+                    candidate::digits(sudoku.bitboard[idx] & mask)
+                        .map(move |digit| Candidate::new(idx, digit))
+                },
+            ));
+        }
+    }
+    eliminations.sort_unstable();
+    eliminations.dedup();
+    eliminations
+}
+
+/// One of the rectangle digits is locked to the roof cells in a shared unit,
+/// so the other digit can be removed from both roof cells.
+fn type_4_eliminations(
+    sudoku: &Sudoku,
+    roof: [usize; 2],
+    digits: [consts::BitWidth; 2],
+) -> Vec<Candidate> {
+    for unit in shared_units(roof) {
+        for (locked, other) in [(digits[0], digits[1]), (digits[1], digits[0])] {
+            if candidate::cells_with_digit(sudoku, unit, locked).all(|idx| roof.contains(&idx)) {
+                return roof.iter().map(|&idx| Candidate::new(idx, other)).collect();
+            }
+        }
+    }
+    vec![]
+}
+
+/// The corner opposite a bivalue corner can not hold the second rectangle
+/// digit if the first digit only appears in the rectangle along its row and
+/// column.
+fn hidden_rectangle_eliminations(
+    sudoku: &Sudoku,
+    cells: [usize; 4],
+    corner: usize,
+    digits: [consts::BitWidth; 2],
+) -> Vec<Candidate> {
+    let Some(&opposite) = cells.iter().find(|&&idx| {
+        idx / consts::WIDTH != corner / consts::WIDTH
+            && idx % consts::WIDTH != corner % consts::WIDTH
+    }) else {
+        return vec![];
+    };
+    let row = &consts::ROWS[opposite / consts::WIDTH];
+    let col = &consts::COLS[opposite % consts::WIDTH];
+    for (locked, other) in [(digits[0], digits[1]), (digits[1], digits[0])] {
+        let only_in_rectangle = |unit| {
+            candidate::cells_with_digit(sudoku, unit, locked).all(|idx| cells.contains(&idx))
+        };
+        if only_in_rectangle(row) && only_in_rectangle(col) {
+            return vec![Candidate::new(opposite, other)];
+        }
+    }
+    vec![]
+}
+
+fn shared_units(cells: [usize; 2]) -> impl Iterator<Item = &'static [usize; consts::WIDTH]> {
+    candidate::units().filter(move |unit| cells.iter().all(|idx| unit.contains(idx)))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::{assert_eliminations_are_sound, with_candidates};
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_not_assumed() {
+        let sudoku = with_candidates(&[(0, &[1, 2]), (3, &[1, 2]), (9, &[1, 2])]);
+        assert!(find_deadly_patterns(&sudoku, Uniqueness::NotAssumed).is_empty());
+    }
+
+    #[cfg(feature = "all_solutions")]
+    #[rstest]
+    #[case(
+        "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..",
+        Uniqueness::Assumed
+    )]
+    #[case(
+        "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7....",
+        Uniqueness::NotAssumed
+    )]
+    fn test_verify(#[case] input: &str, #[case] expected: Uniqueness) -> Result<()> {
+        let sudoku = Sudoku::from_str(input)?;
+        assert_eq!(Uniqueness::verify(&sudoku)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_1() {
+        // r1c1, r1c4, r2c1 are {1, 2}, so r2c4 can be neither
+        let sudoku = with_candidates(&[(0, &[1, 2]), (3, &[1, 2]), (9, &[1, 2])]);
+        let patterns = find_unique_rectangles(&sudoku, Uniqueness::Assumed);
+
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(
+            patterns[0].to_string(),
+            "Unique Rectangle Type 1: 12 in r1c1,r1c4,r2c1,r2c4 => r2c4<>1 r2c4<>2"
+        );
+    }
+
+    #[test]
+    fn test_type_2() {
+        let sudoku = with_candidates(&[
+            (0, &[1, 2]),
+            (3, &[1, 2]),
+            (9, &[1, 2, 3]),
+            (12, &[1, 2, 3]),
+        ]);
+        let patterns = find_unique_rectangles(&sudoku, Uniqueness::Assumed);
+
+        let pattern = patterns
+            .iter()
+            .find(|p| p.kind == DeadlyPatternKind::UniqueRectangle2)
+            .map(|p| p.eliminations.iter().map(|c| c.idx).collect::<Vec<_>>());
+        assert_eq!(pattern, Some(vec![10, 11, 13, 14, 15, 16, 17]));
+    }
+
+    #[test]
+    fn test_type_4() {
+        let mut sudoku = with_candidates(&[
+            (0, &[1, 2]),
+            (3, &[1, 2]),
+            (9, &[1, 2, 3]),
+            (12, &[1, 2, 4]),
+        ]);
+        for idx in consts::ROWS[1] {
+            if idx != 9 && idx != 12 {
+                sudoku.bitboard[idx] &= consts::MASK ^ (1 << 1);
+            }
+        }
+        let patterns = find_unique_rectangles(&sudoku, Uniqueness::Assumed);
+
+        let pattern = patterns
+            .iter()
+            .find(|p| p.kind == DeadlyPatternKind::UniqueRectangle4)
+            .map(|p| p.eliminations.clone());
+        assert_eq!(
+            pattern,
+            Some(vec![Candidate::new(9, 2), Candidate::new(12, 2)])
+        );
+    }
+
+    #[test]
+    fn test_bug_plus_one() -> Result<()> {
+        let mut sudoku = Sudoku::from_str(
+            "050920801020801500618504290541700329870100456036450718465300172080615934193247685",
+        )?;
+        let candidates: [(usize, &[consts::BitWidth]); 22] = [
+            (0, &[3, 7]),
+            (2, &[4, 7]),
+            (5, &[3, 6]),
+            (7, &[4, 6]),
+            (9, &[3, 9]),
+            (11, &[4, 9]),
+            (13, &[6, 7]),
+            (16, &[4, 6]),
+            (17, &[3, 7]),
+            (22, &[3, 7]),
+            (26, &[3, 7]),
+            (31, &[6, 8]),
+            (32, &[6, 8]),
+            (38, &[2, 9]),
+            (40, &[3, 9]),
+            (41, &[2, 3, 9]),
+            (45, &[2, 9]),
+            (50, &[2, 9]),
+            (58, &[8, 9]),
+            (59, &[8, 9]),
+            (63, &[2, 7]),
+            (65, &[2, 7]),
+        ];
+        for (idx, digits) in candidates {
+            sudoku.bitboard[idx] = digits.iter().fold(0, |mask, digit| mask | (1 << digit));
+        }
+
+        let bug = find_bug_plus_one(&sudoku, Uniqueness::Assumed).map(|bug| bug.to_string());
+        assert_eq!(bug.as_deref(), Some("BUG+1: 9 in r5c6 => r5c6<>2 r5c6<>3"));
+        let mut not_grave = sudoku.clone();
+        not_grave.bitboard[0] = (1 << 3) | (1 << 4);
+        assert_eq!(find_bug_plus_one(&not_grave, Uniqueness::Assumed), None);
+        sudoku.bitboard[40] |= 1 << 2;
+        assert_eq!(find_bug_plus_one(&sudoku, Uniqueness::Assumed), None);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "...5..........897..8......49..3.24..6.1..........9.3.67..45..3...3...5....4.7.2..",
+        "397514628415628973286937154978362415631845792542791386729456831163289547854173269"
+    )]
+    #[case(
+        "...4..5.8.....97....1.2...93....5...2.6.9........3..4..42....377....8..186.......",
+        "697413528428569713531827469314685972276194385985732146142956837753248691869371254"
+    )]
+    fn test_eliminations_are_sound(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        assert_eliminations_are_sound(
+            input,
+            expected,
+            |sudoku| find_deadly_patterns(sudoku, Uniqueness::Assumed),
+            |sudoku| apply_deadly_pattern(sudoku, Uniqueness::Assumed),
+            |pattern| &pattern.eliminations,
+        )?;
+        Ok(())
+    }
+}
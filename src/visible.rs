@@ -1,20 +1,26 @@
-use crate::{consts, error::Error, solver::place_and_propagate, sudoku::Sudoku};
+use crate::{
+    board::Board,
+    consts,
+    error::Error,
+    shape::{Shape, MAX_SIZE},
+    solver::place_and_propagate,
+};
 
 /// Place all visible singles in the Sudoku.
 ///
 /// # Errors
 ///
 /// Returns an error if the Sudoku is invalid.
-pub fn place_all_visible_singles(sudoku: &mut Sudoku) -> Result<(), Error> {
-    get_placements(sudoku)
-        .into_iter()
-        .try_for_each(|(idx, digit)| {
-            if sudoku.digits[idx] == 0 {
-                place_and_propagate(sudoku, idx, digit)
-            } else {
-                Ok(())
-            }
-        })
+pub fn place_all_visible_singles<B: Board>(board: &mut B) -> Result<(), Error> {
+    let size = board.shape().size();
+    let singles = get_placements(board);
+    (0..size).try_for_each(|idx| {
+        if singles[idx] != 0 && board.digit(idx) == 0 {
+            place_and_propagate(board, idx, consts::BitWidth::from(singles[idx]))
+        } else {
+            Ok(())
+        }
+    })
 }
 
 /// Place all visible doubles in the Sudoku.
@@ -22,91 +28,82 @@ pub fn place_all_visible_singles(sudoku: &mut Sudoku) -> Result<(), Error> {
 /// # Errors
 ///
 /// Returns an error if the Sudoku is invalid.
-pub fn check_all_visible_doubles(sudoku: &mut Sudoku) -> Result<(), Error> {
-    check_visible_doubles_rows(sudoku)?;
-    check_visible_doubles_cols(sudoku)?;
-    check_visible_doubles_cells(sudoku)
-}
-
-fn get_placements(sudoku: &Sudoku) -> Vec<(usize, consts::BitWidth)> {
-    sudoku
-        .bitboard
-        .iter()
-        .enumerate()
-        .filter(|(_, &bitboard)| bitboard.count_ones() == 1)
-        .map(|(idx, bitboard)| (idx, bitboard.trailing_zeros() as consts::BitWidth))
-        .collect()
-}
-
-fn check_visible_doubles_rows(sudoku: &mut Sudoku) -> Result<(), Error> {
-    check_visible_doubles(sudoku, &consts::SAME_ROW)
-}
-
-fn check_visible_doubles_cols(sudoku: &mut Sudoku) -> Result<(), Error> {
-    check_visible_doubles(sudoku, &consts::SAME_COL)
-}
-
-fn check_visible_doubles_cells(sudoku: &mut Sudoku) -> Result<(), Error> {
-    check_visible_doubles(sudoku, &consts::SAME_CELL)
+pub fn check_all_visible_doubles<B: Board>(board: &mut B) -> Result<(), Error> {
+    let shape = board.shape().clone();
+    // rows, then columns, then boxes, each visited cell by cell
+    for kind in 0..3 {
+        for idx in 0..shape.size() {
+            if board.candidates(idx).count_ones() == 2 {
+                check_visible_double_pairs(board, idx, shape.peers(idx, kind))?;
+            }
+        }
+    }
+    for unit in 3 * shape.width()..shape.num_units() {
+        for &idx in shape.unit(unit) {
+            if board.candidates(idx).count_ones() == 2 {
+                check_visible_double_pairs(board, idx, shape.unit(unit))?;
+            }
+        }
+    }
+    Ok(())
 }
 
-fn check_visible_doubles(
-    sudoku: &mut Sudoku,
-    neighbor_arr: &[[usize; 8]; consts::SIZE],
-) -> Result<(), Error> {
-    for (idx, neighbors) in neighbor_arr.iter().enumerate() {
-        if sudoku.bitboard[idx].count_ones() == 2 {
-            check_visible_double_pairs(sudoku, idx, neighbors)?;
+/// The digit of every cell with a single candidate left, or 0.
+fn get_placements<B: Board>(board: &B) -> [u8; MAX_SIZE] {
+    let mut singles = [0; MAX_SIZE];
+    for (idx, single) in singles.iter_mut().enumerate().take(board.shape().size()) {
+        let bitboard = board.candidates(idx);
+        if bitboard.is_power_of_two() {
+            *single = bitboard.trailing_zeros() as u8;
         }
     }
-    Ok(())
+    singles
 }
 
-fn check_visible_double_pairs(
-    sudoku: &mut Sudoku,
+fn check_visible_double_pairs<B: Board>(
+    board: &mut B,
     idx: usize,
-    neighbors: &[usize; 8],
+    unit: &[usize],
 ) -> Result<(), Error> {
-    for neighbor in neighbors {
-        if sudoku.bitboard[idx] == sudoku.bitboard[*neighbor] {
-            check_visible_double_pair(sudoku, idx, *neighbor, neighbors)?;
+    for &neighbor in unit.iter().filter(|&&other| other != idx) {
+        if board.candidates(idx) == board.candidates(neighbor) {
+            check_visible_double_pair(board, idx, neighbor, unit)?;
         }
     }
     Ok(())
 }
 
-fn check_visible_double_pair(
-    sudoku: &mut Sudoku,
+fn check_visible_double_pair<B: Board>(
+    board: &mut B,
     idx: usize,
     neighbor: usize,
-    neighbors: &[usize; 8],
+    unit: &[usize],
 ) -> Result<(), Error> {
-    let mask = consts::MASK ^ sudoku.bitboard[idx];
-    neighbors
-        .iter()
-        .filter(|&n_idx| *n_idx != neighbor)
-        .try_for_each(|n_idx| check_visible_double(sudoku, *n_idx, mask))
+    let mask = board.shape().mask() ^ board.candidates(idx);
+    unit.iter()
+        .filter(|&&n_idx| n_idx != idx && n_idx != neighbor)
+        .try_for_each(|&n_idx| check_visible_double(board, n_idx, mask))
 }
 
-fn check_visible_double(
-    sudoku: &mut Sudoku,
+fn check_visible_double<B: Board>(
+    board: &mut B,
     n_idx: usize,
     mask: consts::BitWidth,
 ) -> Result<(), Error> {
-    sudoku.bitboard[n_idx] &= mask;
-    check_visible_double_possible(sudoku, n_idx)?;
-    if sudoku.bitboard[n_idx].count_ones() == 1 {
-        let digit = sudoku.bitboard[n_idx].trailing_zeros() as consts::BitWidth;
-        place_and_propagate(sudoku, n_idx, digit)
+    board.keep_candidates(n_idx, mask);
+    check_visible_double_possible(board, n_idx)?;
+    if board.candidates(n_idx).is_power_of_two() {
+        let digit = board.candidates(n_idx).trailing_zeros() as consts::BitWidth;
+        place_and_propagate(board, n_idx, digit)
     } else {
         // TODO: benchmark if it's faster to have this as an else clause
         Ok(())
     }
 }
 
-fn check_visible_double_possible(sudoku: &Sudoku, n_idx: usize) -> Result<(), Error> {
-    if (sudoku.digits[n_idx] == 0) && (sudoku.bitboard[n_idx] == 0) {
-        Err(Error::from(sudoku))
+fn check_visible_double_possible<B: Board>(board: &B, n_idx: usize) -> Result<(), Error> {
+    if (board.digit(n_idx) == 0) && (board.candidates(n_idx) == 0) {
+        Err(Error::NoSolution)
     } else {
         Ok(())
     }
@@ -116,6 +113,7 @@ fn check_visible_double_possible(sudoku: &Sudoku, n_idx: usize) -> Result<(), Er
 #[allow(clippy::panic_in_result_fn)]
 mod tests {
     use super::*;
+    use crate::sudoku::Sudoku;
     use rstest::rstest;
     use std::str::FromStr;
 
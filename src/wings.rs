@@ -0,0 +1,332 @@
+use std::fmt;
+
+use crate::{
+    candidate::{self, eliminations_seen_by, sees, strong_links, Candidate, CellName},
+    consts,
+    sudoku::Sudoku,
+    Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WingKind {
+    XyWing,
+    XyzWing,
+    WWing,
+}
+
+impl fmt::Display for WingKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::XyWing => write!(f, "XY-Wing"),
+            Self::XyzWing => write!(f, "XYZ-Wing"),
+            Self::WWing => write!(f, "W-Wing"),
+        }
+    }
+}
+
+/// The center of a wing: a single cell for XY- and XYZ-Wings, or the strong
+/// link connecting the two pincers of a W-Wing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pivot {
+    Cell(usize),
+    StrongLink {
+        digit: consts::BitWidth,
+        cells: [usize; 2],
+    },
+}
+
+impl fmt::Display for Pivot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Cell(idx) => write!(f, "{}", CellName(*idx)),
+            Self::StrongLink { digit, cells } => {
+                write!(f, "{}={}={}", CellName(cells[0]), digit, CellName(cells[1]))
+            }
+        }
+    }
+}
+
+/// A wing pattern together with the candidates it eliminates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wing {
+    pub kind: WingKind,
+    pub pivot: Pivot,
+    pub pincers: [usize; 2],
+    pub digit: consts::BitWidth,
+    pub eliminations: Vec<Candidate>,
+}
+
+impl fmt::Display for Wing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: pivot {}, pincers {} and {} =>",
+            self.kind,
+            self.pivot,
+            CellName(self.pincers[0]),
+            CellName(self.pincers[1])
+        )?;
+        self.eliminations
+            .iter()
+            .try_for_each(|candidate| write!(f, " {candidate}"))
+    }
+}
+
+/// Find all XY-, XYZ- and W-Wings that eliminate at least one candidate.
+#[must_use]
+pub fn find_wings(sudoku: &Sudoku) -> Vec<Wing> {
+    let mut wings = find_xy_wings(sudoku);
+    wings.extend(find_xyz_wings(sudoku));
+    wings.extend(find_w_wings(sudoku));
+    wings
+}
+
+/// Apply the eliminations of the first wing found, if any.
+///
+/// # Errors
+///
+/// Returns an error if the Sudoku is invalid.
+pub fn apply_wing(sudoku: &mut Sudoku) -> Result<Option<Wing>> {
+    let Some(wing) = find_wings(sudoku).into_iter().next() else {
+        return Ok(None);
+    };
+    candidate::eliminate(sudoku, &wing.eliminations)?;
+    Ok(Some(wing))
+}
+
+/// Find XY-Wings: a bivalue pivot `xy` seeing the pincers `xz` and `yz`.
+/// Every cell seeing both pincers can not be `z`.
+#[must_use]
+pub fn find_xy_wings(sudoku: &Sudoku) -> Vec<Wing> {
+    let mut wings = vec![];
+    for pivot in bivalue_cells(sudoku) {
+        let pivot_mask = sudoku.bitboard[pivot];
+        for &first in &consts::NEIGHBORS[pivot] {
+            let first_mask = sudoku.bitboard[first];
+            if first_mask.count_ones() != 2 || (first_mask & pivot_mask).count_ones() != 1 {
+                continue;
+            }
+            let z_mask = first_mask & !pivot_mask;
+            let second_mask = (pivot_mask & !first_mask) | z_mask;
+            for &second in &consts::NEIGHBORS[pivot] {
+                if second > first && sudoku.bitboard[second] == second_mask {
+                    let digit = z_mask.trailing_zeros() as consts::BitWidth;
+                    push_wing(
+                        sudoku,
+                        &mut wings,
+                        WingKind::XyWing,
+                        Pivot::Cell(pivot),
+                        [first, second],
+                        digit,
+                    );
+                }
+            }
+        }
+    }
+    wings
+}
+
+/// Find XYZ-Wings: a trivalue pivot `xyz` seeing the pincers `xz` and `yz`.
+/// Every cell seeing the pivot and both pincers can not be `z`.
+#[must_use]
+pub fn find_xyz_wings(sudoku: &Sudoku) -> Vec<Wing> {
+    let mut wings = vec![];
+    for pivot in (0..consts::SIZE).filter(|&idx| sudoku.bitboard[idx].count_ones() == 3) {
+        let pivot_mask = sudoku.bitboard[pivot];
+        for &first in &consts::NEIGHBORS[pivot] {
+            let first_mask = sudoku.bitboard[first];
+            if first_mask.count_ones() != 2 || first_mask & !pivot_mask != 0 {
+                continue;
+            }
+            for &second in &consts::NEIGHBORS[pivot] {
+                let second_mask = sudoku.bitboard[second];
+                let is_pincer = second > first
+                    && second_mask.count_ones() == 2
+                    && (first_mask | second_mask) == pivot_mask
+                    && (first_mask & second_mask).is_power_of_two();
+                if is_pincer {
+                    let digit = (first_mask & second_mask).trailing_zeros() as consts::BitWidth;
+                    push_wing(
+                        sudoku,
+                        &mut wings,
+                        WingKind::XyzWing,
+                        Pivot::Cell(pivot),
+                        [first, second],
+                        digit,
+                    );
+                }
+            }
+        }
+    }
+    wings
+}
+
+/// Find W-Wings: two identical bivalue cells `xz` that do not see each other,
+/// connected by a strong link on `x`. Every cell seeing both can not be `z`.
+#[must_use]
+pub fn find_w_wings(sudoku: &Sudoku) -> Vec<Wing> {
+    let mut wings = vec![];
+    let cells: Vec<usize> = bivalue_cells(sudoku).collect();
+    for (i, &first) in cells.iter().enumerate() {
+        for &second in &cells[i + 1..] {
+            let mask = sudoku.bitboard[first];
+            if mask != sudoku.bitboard[second] || sees(first, second) {
+                continue;
+            }
+            for link_digit in candidate::digits(mask) {
+                let Some(link) = find_connecting_link(sudoku, first, second, link_digit) else {
+                    continue;
+                };
+                let digit = (mask ^ (1 << link_digit)).trailing_zeros() as consts::BitWidth;
+                let pivot = Pivot::StrongLink {
+                    digit: link_digit,
+                    cells: link,
+                };
+                push_wing(
+                    sudoku,
+                    &mut wings,
+                    WingKind::WWing,
+                    pivot,
+                    [first, second],
+                    digit,
+                );
+            }
+        }
+    }
+    wings
+}
+
+fn bivalue_cells(sudoku: &Sudoku) -> impl Iterator<Item = usize> + '_ {
+    (0..consts::SIZE).filter(|&idx| sudoku.bitboard[idx].count_ones() == 2)
+}
+
+fn find_connecting_link(
+    sudoku: &Sudoku,
+    first: usize,
+    second: usize,
+    digit: consts::BitWidth,
+) -> Option<[usize; 2]> {
+    strong_links(sudoku, digit)
+        .into_iter()
+        .filter(|link| !link.contains(&first) && !link.contains(&second))
+        .find_map(|[a, b]| {
+            if sees(a, first) && sees(b, second) {
+                Some([a, b])
+            } else if sees(b, first) && sees(a, second) {
+                Some([b, a])
+            } else {
+                None
+            }
+        })
+}
+
+fn push_wing(
+    sudoku: &Sudoku,
+    wings: &mut Vec<Wing>,
+    kind: WingKind,
+    pivot: Pivot,
+    pincers: [usize; 2],
+    digit: consts::BitWidth,
+) {
+    let eliminations = match (kind, pivot) {
+        (WingKind::XyzWing, Pivot::Cell(idx)) => {
+            eliminations_seen_by(sudoku, &[idx, pincers[0], pincers[1]], digit)
+        }
+        _ => eliminations_seen_by(sudoku, &pincers, digit),
+    };
+    if !eliminations.is_empty() {
+        wings.push(Wing {
+            kind,
+            pivot,
+            pincers,
+            digit,
+            eliminations,
+        });
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::candidate::fixtures::{assert_eliminations_are_sound, with_candidates};
+    use rstest::rstest;
+
+    #[test]
+    fn test_xy_wing() {
+        let sudoku = with_candidates(&[(0, &[1, 2]), (4, &[1, 3]), (18, &[2, 3])]);
+        let wings = find_xy_wings(&sudoku);
+
+        assert_eq!(wings.len(), 1);
+        assert_eq!(wings[0].pivot, Pivot::Cell(0));
+        assert_eq!(wings[0].pincers, [4, 18]);
+        let eliminated: Vec<usize> = wings[0].eliminations.iter().map(|c| c.idx).collect();
+        assert_eq!(eliminated, vec![1, 2, 21, 22, 23]);
+        assert!(wings[0].eliminations.iter().all(|c| c.digit == 3));
+    }
+
+    #[test]
+    fn test_xyz_wing() {
+        let sudoku = with_candidates(&[(0, &[1, 2, 3]), (4, &[1, 3]), (18, &[2, 3])]);
+        let wings = find_xyz_wings(&sudoku);
+
+        assert_eq!(wings.len(), 1);
+        let eliminated: Vec<usize> = wings[0].eliminations.iter().map(|c| c.idx).collect();
+        assert_eq!(eliminated, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_w_wing() {
+        let mut sudoku = with_candidates(&[(0, &[1, 2]), (40, &[1, 2])]);
+        // Strong link on 1 in row 9 between r9c1 and r9c5.
+        for idx in consts::ROWS[8] {
+            if idx != 72 && idx != 76 {
+                sudoku.bitboard[idx] &= consts::MASK ^ (1 << 1);
+            }
+        }
+        let wings = find_w_wings(&sudoku);
+
+        assert_eq!(wings.len(), 1);
+        assert_eq!(
+            wings[0].pivot,
+            Pivot::StrongLink {
+                digit: 1,
+                cells: [72, 76]
+            }
+        );
+        assert_eq!(wings[0].digit, 2);
+        let eliminated: Vec<usize> = wings[0].eliminations.iter().map(|c| c.idx).collect();
+        assert_eq!(eliminated, vec![4, 36]);
+    }
+
+    #[test]
+    fn test_display() {
+        let sudoku = with_candidates(&[(0, &[1, 2]), (4, &[1, 3]), (18, &[2, 3])]);
+        let wings = find_xy_wings(&sudoku);
+        assert_eq!(
+            wings[0].to_string(),
+            "XY-Wing: pivot r1c1, pincers r1c5 and r3c1 => r1c2<>3 r1c3<>3 r3c4<>3 r3c5<>3 r3c6<>3"
+        );
+    }
+
+    #[rstest]
+    #[case(
+        ".......124...129....5.6.8....26....7......3.174.3.....3.94..2....6.2...........59",
+        "693784512487512936125963874932651487568247391741398625319475268856129743274836159"
+    )]
+    #[case(
+        "14..7..3...7.9....2.5..1..8.14.6.........9......4...7.5..74.2......153.........6.",
+        "148572936637894152295631748314267895756189423829453671583746219462915387971328564"
+    )]
+    #[case(
+        "...94............25...2.3.11..8..6..3....1.....953...4.3.19..4...6...8....5.....3",
+        "621943758783615492594728361142879635357461289869532174238197546916354827475286913"
+    )]
+    fn test_eliminations_are_sound(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        assert_eliminations_are_sound(input, expected, find_wings, apply_wing, |wing| {
+            &wing.eliminations
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    str::FromStr,
+};
+
+use rust_sudoku_solver::{solver, Result, Sudoku};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations made by the current thread, so that tests running
+/// in parallel don't affect each other.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        // SAFETY: forwarded as is
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: forwarded as is
+        unsafe { System.dealloc(ptr, layout) };
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        // SAFETY: forwarded as is
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+#[allow(clippy::panic_in_result_fn)]
+fn test_solve_does_not_allocate() -> Result<()> {
+    let puzzles = [
+        "057000300000801000001000000600030090020070000800000000400600000000000207000000050",
+        "000000036030000050200000000000060800700000400000053000000700210060900000001000000",
+        "........8..3...4...9..2..6.....79.......612...6.5.2.7...8...5...1.....2.4.5.....3",
+        "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+    ];
+    for puzzle in puzzles {
+        let sudoku = Sudoku::from_str(puzzle)?;
+        let (result, allocations) = allocations_during(|| solver::solve(sudoku));
+        let (solution, _) = result?;
+
+        assert!(solution.is_solved());
+        assert_eq!(allocations, 0, "{puzzle}");
+    }
+    Ok(())
+}